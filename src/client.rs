@@ -1,25 +1,46 @@
 use crate::{
     app_id::{AppId, RefAppId},
-    filter::{CommonFilter, Filterable, CommonFilterKind},
+    filter::{CommonFilter, CommonFilterKind, Filterable},
     host::Host,
-    model::task::{TaskId, TaskStatus},
-    request::{BatchWriteRequests, PartialUpdateQuery, SearchQuery, SetSettings},
+    insights::InsightsClient,
+    model::{
+        rule::Rule,
+        synonym::Synonym,
+        task::{TaskId, TaskStatus, WaitTaskOptions},
+    },
+    recommend::{RecommendResponse, RecommendationsQuery},
+    request::{
+        BatchWriteRequest, BatchWriteRequests, CopyScope, GetObjectRequest, IndexOperation,
+        PartialUpdateBody, PartialUpdateQuery, ReplicaWriteQuery, SearchQuery, SetSettings,
+    },
     response::{
-        BatchWriteResponse, ObjectDeleteResponse, ObjectUpdateResponse, SearchResponse,
-        SettingsUpdateResponse, TaskStatusResponse,
+        BatchImportSummary, BatchWriteResponse, BrowseResponse, Conditional, DefaultFacets,
+        DeleteByFilterResponse, FlattenEmpty, GetObjectsResponse, ImportLineError, IndexInfo,
+        IndexOperationResponse, IndexSettings, ObjectCreateResponse, ObjectDeleteResponse,
+        ObjectUpdateResponse, RuleDeleteResponse, RulesUpdateResponse, SearchResponse,
+        SettingsUpdateResponse, SynonymDeleteResponse, SynonymsUpdateResponse, TaskStatusResponse,
     },
+    stats::SearchStats,
     ApiKey, Error, Result, HOST_FALLBACK_LIST,
 };
 use rand::seq::SliceRandom;
 use reqwest::{
-    header::{HeaderMap, HeaderValue},
-    StatusCode,
+    header::{HeaderMap, HeaderValue, ETAG, IF_NONE_MATCH},
+    Method, StatusCode,
 };
 use serde::de::DeserializeOwned;
-use std::{fmt, future::Future, time::Duration};
+use std::{
+    fmt,
+    future::Future,
+    time::{Duration, Instant},
+};
+use tokio::io::{AsyncBufRead, AsyncBufReadExt, AsyncWrite, AsyncWriteExt};
 
 // todo: make the ApiKey a `RefApiKey`
-fn reqwest_client(app_id: &RefAppId, api_key: &ApiKey) -> reqwest::Result<reqwest::Client> {
+pub(crate) fn reqwest_client(
+    app_id: &RefAppId,
+    api_key: &ApiKey,
+) -> reqwest::Result<reqwest::Client> {
     let mut headers = HeaderMap::new();
 
     headers.append(
@@ -34,7 +55,16 @@ fn reqwest_client(app_id: &RefAppId, api_key: &ApiKey) -> reqwest::Result<reqwes
 
     headers.append("X-Algolia-API-Key", api_key_header);
 
-    reqwest::ClientBuilder::new()
+    let builder = reqwest::ClientBuilder::new();
+
+    // `native-tls` wins if both are enabled, since `rustls-tls` is a default feature.
+    #[cfg(feature = "native-tls")]
+    let builder = builder.use_native_tls();
+
+    #[cfg(all(feature = "rustls-tls", not(feature = "native-tls")))]
+    let builder = builder.use_rustls_tls();
+
+    builder
         .default_headers(headers)
         .timeout(Duration::from_secs(10))
         .connect_timeout(Duration::from_secs(5))
@@ -47,6 +77,14 @@ enum IndexRouteKind {
     Query,
     Settings,
     Batch,
+    SynonymsBatch,
+    SynonymsClear,
+    RulesBatch,
+    RulesClear,
+    Objects,
+    Browse,
+    Recommendations,
+    Operation,
 }
 
 impl fmt::Display for IndexRouteKind {
@@ -55,10 +93,60 @@ impl fmt::Display for IndexRouteKind {
             Self::Query => f.write_str("query"),
             Self::Settings => f.write_str("settings"),
             Self::Batch => f.write_str("batch"),
+            Self::SynonymsBatch => f.write_str("synonyms/batch"),
+            Self::SynonymsClear => f.write_str("synonyms/clear"),
+            Self::RulesBatch => f.write_str("rules/batch"),
+            Self::RulesClear => f.write_str("rules/clear"),
+            Self::Objects => f.write_str("objects"),
+            Self::Browse => f.write_str("browse"),
+            Self::Recommendations => f.write_str("recommendations"),
+            Self::Operation => f.write_str("operation"),
         }
     }
 }
 
+/// A route of the API, as formatted after `/1/`.
+pub(crate) trait Route: fmt::Display {
+    /// Whether sending the same request twice has the same effect as sending it once.
+    ///
+    /// Requests to non-idempotent routes aren't retried on another host after a timeout (or a server error),
+    /// since the first attempt may have been applied server side.
+    fn idempotent(&self) -> bool {
+        true
+    }
+
+    /// Whether the route only reads, in which case it's first sent to the application's DSN host,
+    /// which serves reads from the closest replica.
+    fn read(&self) -> bool {
+        false
+    }
+
+    /// The host of the API serving this route, when it isn't the search API of the application
+    /// (e.g. `Host::insights()`). It's the only host tried, since these APIs have no fallbacks.
+    fn service_host(&self) -> Option<Host<'static>> {
+        None
+    }
+}
+
+/// A route whose idempotency depends on the request rather than on the route itself,
+/// e.g. a batch is only non-idempotent if it auto-generates object IDs.
+struct WithIdempotency<T> {
+    route: T,
+    idempotent: bool,
+}
+
+impl<T: fmt::Display> fmt::Display for WithIdempotency<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.route.fmt(f)
+    }
+}
+
+impl<T: fmt::Display> Route for WithIdempotency<T> {
+    fn idempotent(&self) -> bool {
+        self.idempotent
+    }
+}
+
 struct IndexRoute<'a> {
     index_name: &'a str,
     kind: Option<IndexRouteKind>,
@@ -76,6 +164,20 @@ impl fmt::Display for IndexRoute<'_> {
     }
 }
 
+impl Route for IndexRoute<'_> {
+    fn read(&self) -> bool {
+        matches!(
+            self.kind,
+            Some(
+                IndexRouteKind::Query
+                    | IndexRouteKind::Browse
+                    | IndexRouteKind::Objects
+                    | IndexRouteKind::Recommendations
+            )
+        )
+    }
+}
+
 struct ObjectRoute<'a> {
     index_name: &'a str,
     object_id: &'a str,
@@ -94,6 +196,42 @@ impl fmt::Display for ObjectRoute<'_> {
     }
 }
 
+impl Route for ObjectRoute<'_> {}
+
+#[derive(Copy, Clone)]
+enum Dictionary {
+    Synonyms,
+    Rules,
+}
+
+impl fmt::Display for Dictionary {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Synonyms => f.write_str("synonyms"),
+            Self::Rules => f.write_str("rules"),
+        }
+    }
+}
+
+/// A single synonym or rule.
+struct DictionaryEntryRoute<'a> {
+    index_name: &'a str,
+    dictionary: Dictionary,
+    object_id: &'a str,
+}
+
+impl fmt::Display for DictionaryEntryRoute<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "indexes/{}/{}/{}",
+            self.index_name, self.dictionary, self.object_id
+        )
+    }
+}
+
+impl Route for DictionaryEntryRoute<'_> {}
+
 struct TaskRoute<'a> {
     index_name: &'a str,
     task_id: TaskId,
@@ -107,14 +245,28 @@ impl fmt::Display for TaskRoute<'_> {
     }
 }
 
+impl Route for TaskRoute<'_> {}
+
 #[derive(Clone, Debug)]
 pub struct Client {
     client: reqwest::Client,
     application_id: AppId,
+    // kept around for when we need to rebuild the client.
+    #[allow(dead_code)]
     api_key: ApiKey,
+    /// Extra headers sent with every request, see `Client::with_headers`.
+    headers: HeaderMap,
+    /// Default `X-Algolia-UserToken` for searches, see `Client::with_user_token`.
+    user_token: Option<String>,
+    /// Replaces the algolia hosts, see `Client::with_base_url`.
+    base_url: Option<String>,
+    /// Records the latency of searches, see `Client::with_search_stats`.
+    search_stats: Option<SearchStats>,
 }
 
-async fn decode<T: DeserializeOwned>(resp: reqwest::Response) -> Result<Option<T>, Error> {
+pub(crate) async fn decode<T: DeserializeOwned>(
+    resp: reqwest::Response,
+) -> Result<Option<T>, Error> {
     resp.json()
         .await
         .map(Some)
@@ -131,7 +283,7 @@ macro_rules! unwrap_ret {
     };
 }
 
-async fn check_response(
+pub(crate) async fn check_response(
     resp: reqwest::Result<reqwest::Response>,
     index: Option<&str>,
 ) -> Result<Option<reqwest::Response>, Error> {
@@ -163,7 +315,42 @@ async fn check_response(
     Ok(Some(resp))
 }
 
+/// A line of `Client::import_ndjson`, as its `objectID` and the rest of the object.
+fn parse_ndjson_object(
+    line: &str,
+) -> Result<(String, serde_json::Map<String, serde_json::Value>), String> {
+    let mut object: serde_json::Map<String, serde_json::Value> =
+        serde_json::from_str(line).map_err(|it| it.to_string())?;
+
+    match object.remove("objectID") {
+        Some(serde_json::Value::String(object_id)) => Ok((object_id, object)),
+        Some(_) => Err("`objectID` isn't a string".to_owned()),
+        None => Err("missing `objectID`".to_owned()),
+    }
+}
+
+/// Split `requests` into batches of at most `chunk_size` operations, keeping their order.
+fn chunk_batch(
+    requests: Vec<BatchWriteRequest>,
+    chunk_size: usize,
+) -> impl Iterator<Item = BatchWriteRequests> {
+    assert!(chunk_size > 0, "`chunk_size` must be greater than 0");
+
+    let mut requests = requests.into_iter().peekable();
+
+    std::iter::from_fn(move || {
+        requests.peek()?;
+
+        Some(BatchWriteRequests {
+            requests: requests.by_ref().take(chunk_size).collect(),
+        })
+    })
+}
+
 impl Client {
+    /// A batch size that stays well within algolia's limits, for use with `batch_chunked`.
+    pub const DEFAULT_BATCH_CHUNK_SIZE: usize = 1000;
+
     pub fn new(application_id: AppId, api_key: ApiKey) -> Result<Self> {
         let client = reqwest_client(&application_id, &api_key)
             .map_err(|it| Error::Configuration(Box::new(it)))?;
@@ -172,11 +359,62 @@ impl Client {
             client,
             application_id,
             api_key,
+            headers: HeaderMap::new(),
+            user_token: None,
+            base_url: None,
+            search_stats: None,
         })
     }
 
-    async fn retry_with<
-        T: fmt::Display,
+    /// Send `headers` with every request made by this client, replacing any header with the same name.
+    ///
+    /// This is meant for deployments behind a proxy or gateway that requires extra headers.
+    /// Since `Client` is cheap to clone, headers for a single call can be set on a clone:
+    /// `client.clone().with_headers(headers).search(...)`.
+    pub fn with_headers(mut self, headers: HeaderMap) -> Self {
+        self.headers.extend(headers);
+        self
+    }
+
+    /// An `InsightsClient` for the same application, sending the same extra headers (see `Client::with_headers`).
+    pub fn insights(&self) -> InsightsClient {
+        InsightsClient::with_client(self.clone())
+    }
+
+    /// Send `user_token` as the `X-Algolia-UserToken` header of every search,
+    /// unless the `SearchQuery` has its own `user_token`.
+    ///
+    /// Algolia uses it to rate-limit and personalize per user rather than per IP,
+    /// which matters when all the searches come from a backend.
+    pub fn with_user_token(mut self, user_token: String) -> Self {
+        self.user_token = Some(user_token);
+        self
+    }
+
+    /// Send every request to `base_url` (e.g. `http://localhost:8080`, without a trailing `/`)
+    /// instead of the application's algolia hosts, typically a mock server in tests.
+    ///
+    /// Requests are still retried the same way, each retry goes to `base_url` too.
+    pub fn with_base_url(mut self, base_url: String) -> Self {
+        self.base_url = Some(base_url);
+        self
+    }
+
+    /// Record the latency of every successful search into `stats`,
+    /// keep a clone of it to read the totals.
+    pub fn with_search_stats(mut self, stats: SearchStats) -> Self {
+        self.search_stats = Some(stats);
+        self
+    }
+
+    pub(crate) fn request(&self, method: Method, url: &str) -> reqwest::RequestBuilder {
+        self.client
+            .request(method, url)
+            .headers(self.headers.clone())
+    }
+
+    pub(crate) async fn retry_with<
+        T: Route,
         O,
         Fut: Future<Output=Result<Option<O>>>,
         Fn: FnMut(String) -> Fut,
@@ -185,36 +423,77 @@ impl Client {
         route: T,
         mut f: Fn,
     ) -> Result<O> {
-        let mut fallback_order = HOST_FALLBACK_LIST.to_vec();
-        fallback_order.shuffle(&mut rand::thread_rng());
-
-        for backup_number in std::iter::once(0).chain(fallback_order.iter().copied()) {
-            match f(format!(
-                "https://{}/1/{}",
-                Host::with_backup(&self.application_id, Some(backup_number)),
-                &route,
-            ))
-                .await
-            {
+        let hosts = match route.service_host() {
+            Some(host) => vec![host],
+            None => {
+                let mut fallback_order = HOST_FALLBACK_LIST.to_vec();
+                fallback_order.shuffle(&mut rand::thread_rng());
+
+                std::iter::once(Host::with_dsn(&self.application_id, route.read()))
+                    .chain(
+                        fallback_order
+                            .into_iter()
+                            .map(|it| Host::with_backup(&self.application_id, Some(it))),
+                    )
+                    .collect()
+            }
+        };
+
+        let mut attempts = 0;
+
+        for host in hosts {
+            attempts += 1;
+
+            let url = match &self.base_url {
+                Some(base_url) => format!("{}/1/{}", base_url, &route),
+                None => format!("https://{}/1/{}", host, &route),
+            };
+
+            match f(url).await {
+                Ok(None) if !route.idempotent() => break,
                 Ok(None) => continue,
                 Ok(Some(res)) => return Ok(res),
                 Err(e) => return Err(e),
             }
         }
 
-        Err(Error::Timeout)
+        Err(Error::Timeout {
+            route: route.to_string(),
+            attempts,
+        })
     }
 
-    pub async fn batch(&self, index: &str, req: &BatchWriteRequests) -> Result<BatchWriteResponse> {
+    /// Perform multiple write operations at once.
+    ///
+    /// If `req` contains `BatchWriteRequest::AddObject`s, it isn't retried on another host after a timeout,
+    /// since retrying could create the objects twice.
+    ///
+    /// `timeout` replaces the default 10s timeout of each attempt, for batches that take longer than that.
+    pub async fn batch(
+        &self,
+        index: &str,
+        req: &BatchWriteRequests,
+        timeout: Option<Duration>,
+    ) -> Result<BatchWriteResponse> {
         self.retry_with(
-            IndexRoute {
-                index_name: index,
-                kind: Some(IndexRouteKind::Batch),
+            WithIdempotency {
+                route: IndexRoute {
+                    index_name: index,
+                    kind: Some(IndexRouteKind::Batch),
+                },
+                idempotent: !req
+                    .requests
+                    .iter()
+                    .any(|it| matches!(it, BatchWriteRequest::AddObject { .. })),
             },
             |url| async move {
-                let resp = unwrap_ret!(
-                    check_response(self.client.post(&url).json(req).send().await, None).await
-                );
+                let mut request = self.request(Method::POST, &url).json(req);
+
+                if let Some(timeout) = timeout {
+                    request = request.timeout(timeout);
+                }
+
+                let resp = unwrap_ret!(check_response(request.send().await, None).await);
 
                 decode(resp).await
             },
@@ -222,149 +501,307 @@ impl Client {
             .await
     }
 
-    pub async fn set_settings(
+    /// Same as `batch`, but split into sequential batches of at most `chunk_size` operations,
+    /// to avoid having large imports rejected by algolia.
+    /// `Client::DEFAULT_BATCH_CHUNK_SIZE` is a sensible default.
+    ///
+    /// Returns one response per batch, in order, so the `object_ids` of all the responses chained together
+    /// match the order of `requests`.
+    /// If a batch fails, the previous ones have still been applied.
+    ///
+    /// `timeout` applies to each batch, see `batch`.
+    ///
+    /// # Panics
+    /// If `chunk_size` is 0.
+    pub async fn batch_chunked(
         &self,
         index: &str,
-        req: &SetSettings,
-    ) -> Result<SettingsUpdateResponse> {
-        self.retry_with(
-            IndexRoute {
-                index_name: index,
-                kind: Some(IndexRouteKind::Settings),
-            },
-            |url| async move {
-                let resp = unwrap_ret!(
-                    check_response(self.client.put(&url).json(req).send().await, None).await
-                );
+        requests: Vec<BatchWriteRequest>,
+        chunk_size: usize,
+        timeout: Option<Duration>,
+    ) -> Result<Vec<BatchWriteResponse>> {
+        let mut responses = Vec::new();
 
-                decode(resp).await
-            },
-        )
+        for chunk in chunk_batch(requests, chunk_size) {
+            responses.push(self.batch(index, &chunk, timeout).await?);
+        }
+
+        Ok(responses)
+    }
+
+    /// Delete the objects with these IDs, in a single batch.
+    pub async fn delete_objects(
+        &self,
+        index: &str,
+        object_ids: &[String],
+    ) -> Result<BatchWriteResponse> {
+        self.delete_objects_with_timeout(index, object_ids, None)
             .await
     }
 
-    pub async fn task_status(&self, index: &str, task_id: TaskId) -> Result<TaskStatus> {
-        self.retry_with(
-            TaskRoute {
-                index_name: index,
-                task_id,
-            },
-            |url| async move {
-                let resp =
-                    unwrap_ret!(check_response(self.client.get(&url).send().await, None).await);
+    async fn delete_objects_with_timeout(
+        &self,
+        index: &str,
+        object_ids: &[String],
+        timeout: Option<Duration>,
+    ) -> Result<BatchWriteResponse> {
+        let requests = object_ids
+            .iter()
+            .map(|object_id| BatchWriteRequest::DeleteObject {
+                object_id: object_id.clone(),
+            })
+            .collect();
 
-                decode::<TaskStatusResponse>(resp)
-                    .await
-                    .map(|it| it.map(|it| it.status))
-            },
-        )
+        self.batch(index, &BatchWriteRequests { requests }, timeout)
             .await
     }
 
-    #[inline(always)]
-    pub async fn search<T: CommonFilterKind, U: Filterable, V: DeserializeOwned>(
+    /// Delete every object matching `filters`, by browsing the index for their IDs then deleting them
+    /// with `delete_objects`, in batches of `Client::DEFAULT_BATCH_CHUNK_SIZE`.
+    ///
+    /// Unlike `deleteByQuery`, this reports which objects were deleted, and works with API keys that can only
+    /// browse and delete objects.
+    /// Since it reads then deletes, objects that start matching `filters` in between aren't deleted,
+    /// and objects that stop matching still are.
+    ///
+    /// `timeout` applies to each delete batch, see `batch`.
+    pub async fn delete_objects_by_filter<U: Filterable>(
         &self,
         index: &str,
-        request: SearchQuery<'_, T, U>,
-    ) -> Result<SearchResponse<V>> {
-        let optional_filters = request
-            .optional_filters
-            .as_deref()
-            .unwrap_or_default()
-            .iter()
-            .map(|it: &CommonFilter<T>| format!("{}", it))
-            .collect::<Vec<_>>();
+        filters: U,
+        timeout: Option<Duration>,
+    ) -> Result<DeleteByFilterResponse> {
+        let query = SearchQuery::<String, U> {
+            filters: Some(filters),
+            hits_per_page: Some(1000),
+            attributes_to_retrieve: Some(vec!["objectID".to_owned()]),
+            ..SearchQuery::default()
+        };
 
-        let request = serde_urlencoded::to_string(request).expect("request should be serializable");
-        let request = &*request;
+        let mut object_ids = Vec::new();
+        let mut cursor = None;
+
+        loop {
+            let page: BrowseResponse = self.browse(index, &query, cursor.as_deref()).await?;
+            object_ids.extend(page.hits.into_iter().map(|it| it.object_id));
+
+            cursor = page.cursor;
+            if cursor.is_none() {
+                break;
+            }
+        }
+
+        let mut batches = Vec::new();
+        for chunk in object_ids.chunks(Self::DEFAULT_BATCH_CHUNK_SIZE) {
+            batches.push(
+                self.delete_objects_with_timeout(index, chunk, timeout)
+                    .await?,
+            );
+        }
 
-        self.search_inner(index, request, &optional_filters).await
+        Ok(DeleteByFilterResponse {
+            object_ids,
+            batches,
+        })
     }
 
-    // Wrapped by `search`. But removes of the generic arguments
-    // to avoid more instantiations of this function than needed.
-    async fn search_inner<T: DeserializeOwned, U: AsRef<str>>(
+    /// Retrieve a page of all the objects matching `request` (ignoring relevance), without the 1000 hits limit of `search`.
+    ///
+    /// The first page is retrieved with `cursor: None`, the next ones with the `cursor` of the previous page,
+    /// until there's none. `request` is ignored when a `cursor` is given, since the cursor encodes it.
+    ///
+    /// Alternatively, pages can be picked with `SearchQuery::offset` and `SearchQuery::length` (always with `cursor: None`),
+    /// which makes them reproducible, but objects added or removed in between requests can shift the offsets,
+    /// so objects can be missed or returned twice. The cursor is consistent.
+    pub async fn browse<T: CommonFilterKind, U: Filterable, V: DeserializeOwned>(
         &self,
         index: &str,
-        request: &str,
-        optional_filters: &[U],
-    ) -> Result<SearchResponse<T>> {
+        request: &SearchQuery<'_, T, U>,
+        cursor: Option<&str>,
+    ) -> Result<BrowseResponse<V>> {
         #[derive(serde::Serialize)]
-        #[serde(rename_all = "camelCase")]
         struct Request<'a> {
             params: &'a str,
-            optional_filters: &'a [&'a str],
+            #[serde(skip_serializing_if = "Option::is_none")]
+            cursor: Option<&'a str>,
         }
 
-        let optional_filters = &optional_filters.into_iter().map(|it| it.as_ref()).collect::<Vec<_>>();
+        let params = &*request.to_params_string();
 
         self.retry_with(
             IndexRoute {
                 index_name: index,
-                kind: Some(IndexRouteKind::Query),
+                kind: Some(IndexRouteKind::Browse),
             },
             |url| async move {
-                let mut req = self.client.post(&url);
-
-                req = req.json(&Request { params: request, optional_filters });
-
-                let resp = unwrap_ret!(check_response(req.send().await, Some(index)).await);
+                let resp = unwrap_ret!(
+                    check_response(
+                        self.request(Method::POST, &url)
+                            .json(&Request { params, cursor })
+                            .send()
+                            .await,
+                        Some(index)
+                    )
+                    .await
+                );
 
                 decode(resp).await
             },
         )
+        .await
+    }
+
+    /// Write every object of `index` to `writer`, as newline-delimited JSON, and return how many were written.
+    ///
+    /// This is the usual format for backups, see `Client::import_ndjson` to restore them.
+    /// Each page of the browse is written at once, so if writing fails (with `Error::IoError`),
+    /// `writer` contains the objects of the previous pages, followed by a part of the failed page.
+    pub async fn export_ndjson<W: AsyncWrite + Unpin>(
+        &self,
+        index: &str,
+        mut writer: W,
+    ) -> Result<usize> {
+        let query = SearchQuery::<String> {
+            hits_per_page: Some(1000),
+            ..SearchQuery::default()
+        };
+
+        let mut count = 0;
+        let mut cursor = None;
+        let mut buffer = Vec::new();
+
+        loop {
+            let page: BrowseResponse<serde_json::Map<String, serde_json::Value>> =
+                self.browse(index, &query, cursor.as_deref()).await?;
+
+            buffer.clear();
+            for hit in page.hits {
+                let mut object = hit.inner;
+                object.insert("objectID".to_owned(), hit.object_id.into());
+
+                serde_json::to_writer(&mut buffer, &object)
+                    .map_err(|it| Error::EncodeError(Box::new(it)))?;
+                buffer.push(b'\n');
+                count += 1;
+            }
+
+            writer
+                .write_all(&buffer)
+                .await
+                .map_err(|it| Error::IoError(Box::new(it)))?;
+
+            cursor = page.cursor;
+            if cursor.is_none() {
+                break;
+            }
+        }
+
+        writer
+            .flush()
             .await
+            .map_err(|it| Error::IoError(Box::new(it)))?;
+
+        Ok(count)
     }
 
-    /// Add or replace an object with a given object ID.
-    /// If the object does not exist, it will be created. If it already exists, it will be replaced.
-    pub async fn add_or_update_object<T: serde::Serialize>(
+    /// Add or replace the objects read from `reader`, one JSON object per line (as written by `export_ndjson`),
+    /// in batches of `Client::DEFAULT_BATCH_CHUNK_SIZE` sent as the lines are read.
+    ///
+    /// Lines that aren't a JSON object with a string `objectID` are skipped and reported in the summary,
+    /// instead of aborting the import. Blank lines are ignored.
+    /// Reading errors and failed batches do abort it, the previous batches having been applied.
+    pub async fn import_ndjson<R: AsyncBufRead + Unpin>(
         &self,
         index: &str,
-        object_id: &str,
-        body: &T,
-    ) -> Result<ObjectUpdateResponse> {
-        self.retry_with(
-            ObjectRoute {
-                index_name: index,
-                object_id,
-                partial: false,
-            },
-            |url| async move {
-                let resp = unwrap_ret!(
-                    check_response(self.client.put(&url).json(body).send().await, None).await
-                );
+        reader: R,
+    ) -> Result<BatchImportSummary> {
+        let mut summary = BatchImportSummary::default();
+        let mut requests = Vec::new();
+        let mut lines = reader.lines();
+        let mut line_number = 0;
 
-                decode(resp).await
-            },
-        )
+        while let Some(line) = lines
+            .next_line()
             .await
+            .map_err(|it| Error::IoError(Box::new(it)))?
+        {
+            line_number += 1;
+
+            if line.trim().is_empty() {
+                continue;
+            }
+
+            match parse_ndjson_object(&line) {
+                Ok((object_id, body)) => {
+                    requests.push(BatchWriteRequest::UpdateObject { body, object_id })
+                }
+                Err(message) => summary.errors.push(ImportLineError {
+                    line: line_number,
+                    message,
+                }),
+            }
+
+            if requests.len() == Self::DEFAULT_BATCH_CHUNK_SIZE {
+                let requests = std::mem::take(&mut requests);
+                summary.imported += requests.len();
+                let batch = BatchWriteRequests { requests };
+                summary.batches.push(self.batch(index, &batch, None).await?);
+            }
+        }
+
+        if !requests.is_empty() {
+            summary.imported += requests.len();
+            let batch = BatchWriteRequests { requests };
+            summary.batches.push(self.batch(index, &batch, None).await?);
+        }
+
+        Ok(summary)
     }
 
-    /// Partially update an object.
+    /// The number of objects in `index` and its settings, retrieved concurrently.
     ///
-    /// This creates a brand new record if it doesn’t already exist (and the createIfNotExists parameter isn’t set to false).
+    /// Algolia has no endpoint for the number of objects of a single index, the `nbHits` of an empty query
+    /// (made here with `hitsPerPage=0`) is the canonical way to get it.
+    /// Like any search, it's counted in the index's analytics.
+    pub async fn index_info(&self, index: &str) -> Result<IndexInfo> {
+        let query = SearchQuery::<String> {
+            hits_per_page: Some(0),
+            ..SearchQuery::default()
+        };
+
+        let (count, settings) = tokio::try_join!(
+            self.search::<_, _, FlattenEmpty, DefaultFacets>(index, query),
+            self.get_settings(index)
+        )?;
+
+        Ok(IndexInfo {
+            object_count: count.hit_count,
+            settings,
+        })
+    }
+
+    /// Copy `source` to `destination`, replacing it. Only the parts of the index in `scope` are copied,
+    /// or the whole index (objects included) if `scope` is empty.
     ///
-    /// You can pass any first-level attribute you want to add or replace within the record, but you can’t individually update nested attributes.
-    /// If you specify a nested attribute, the engine treats it as a replacement of its first-level ancestor.
-    pub async fn partially_update_object<T: serde::Serialize>(
+    /// The returned task is on `source`: `client.wait_for_task(source, resp.task_id)`.
+    pub async fn copy_index(
         &self,
-        index: &str,
-        object_id: &str,
-        body: &T,
-        query: &PartialUpdateQuery,
-    ) -> Result<ObjectUpdateResponse> {
+        source: &str,
+        destination: &str,
+        scope: &[CopyScope],
+    ) -> Result<IndexOperationResponse> {
+        let body = &IndexOperation::copy(destination, scope);
+
         self.retry_with(
-            ObjectRoute {
-                index_name: index,
-                object_id,
-                partial: true,
+            IndexRoute {
+                index_name: source,
+                kind: Some(IndexRouteKind::Operation),
             },
             |url| async move {
                 let resp = unwrap_ret!(
                     check_response(
-                        self.client.post(&url).query(query).json(body).send().await,
-                        None
+                        self.request(Method::POST, &url).json(body).send().await,
+                        Some(source)
                     )
                     .await
                 );
@@ -372,28 +809,1671 @@ impl Client {
                 decode(resp).await
             },
         )
+        .await
+    }
+
+    /// Replace the settings of `destination` with the ones of `source`, e.g. to promote them from staging.
+    pub async fn copy_settings(
+        &self,
+        source: &str,
+        destination: &str,
+    ) -> Result<IndexOperationResponse> {
+        self.copy_index(source, destination, &[CopyScope::Settings])
             .await
     }
 
-    /// Delete an existing object from an index.
-    pub async fn delete_object(
+    /// Replace the synonyms of `destination` with the ones of `source`.
+    pub async fn copy_synonyms(
         &self,
-        index: &str,
-        object_id: &str,
-    ) -> Result<ObjectDeleteResponse> {
+        source: &str,
+        destination: &str,
+    ) -> Result<IndexOperationResponse> {
+        self.copy_index(source, destination, &[CopyScope::Synonyms])
+            .await
+    }
+
+    /// Replace the Query Rules of `destination` with the ones of `source`.
+    pub async fn copy_rules(
+        &self,
+        source: &str,
+        destination: &str,
+    ) -> Result<IndexOperationResponse> {
+        self.copy_index(source, destination, &[CopyScope::Rules])
+            .await
+    }
+
+    /// Retrieve the current settings of `index`.
+    pub async fn get_settings(&self, index: &str) -> Result<IndexSettings> {
         self.retry_with(
-            ObjectRoute {
+            IndexRoute {
                 index_name: index,
-                object_id,
-                partial: false,
+                kind: Some(IndexRouteKind::Settings),
             },
             |url| async move {
-                let resp =
-                    unwrap_ret!(check_response(self.client.delete(&url).send().await, None).await);
+                let resp = unwrap_ret!(
+                    check_response(self.request(Method::GET, &url).send().await, Some(index)).await
+                );
 
                 decode(resp).await
             },
         )
-            .await
+        .await
+    }
+
+    /// Whether `index` exists.
+    pub async fn index_exists(&self, index: &str) -> Result<bool> {
+        match self.get_settings(index).await {
+            Ok(_) => Ok(true),
+            Err(Error::IndexNotFound(_)) => Ok(false),
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Apply `settings` to `index`, creating it if it doesn't exist, and wait for them to be applied.
+    ///
+    /// Setting the settings of a missing index creates it, so this also works with `SetSettings::patch()`,
+    /// to make sure an index exists before using it.
+    pub async fn ensure_index(&self, index: &str, settings: &SetSettings) -> Result<()> {
+        let resp = self.set_settings(index, settings).await?;
+
+        self.wait_for_task(index, resp.task_id).await
+    }
+
+    /// Change the settings of an index, only the fields set in `req` are changed.
+    pub async fn set_settings(
+        &self,
+        index: &str,
+        req: &SetSettings,
+    ) -> Result<SettingsUpdateResponse> {
+        self.retry_with(
+            IndexRoute {
+                index_name: index,
+                kind: Some(IndexRouteKind::Settings),
+            },
+            |url| async move {
+                let resp = unwrap_ret!(
+                    check_response(self.request(Method::PUT, &url).json(req).send().await, None)
+                        .await
+                );
+
+                decode(resp).await
+            },
+        )
+            .await
+    }
+
+    pub async fn task_status(&self, index: &str, task_id: TaskId) -> Result<TaskStatus> {
+        self.task(index, task_id).await.map(|it| it.status)
+    }
+
+    async fn task(&self, index: &str, task_id: TaskId) -> Result<TaskStatusResponse> {
+        self.retry_with(
+            TaskRoute {
+                index_name: index,
+                task_id,
+            },
+            |url| async move {
+                let resp = unwrap_ret!(
+                    check_response(self.request(Method::GET, &url).send().await, None).await
+                );
+
+                decode(resp).await
+            },
+        )
+        .await
+    }
+
+    /// Same as `task_status`, named like the official clients' `getTask`.
+    pub async fn get_task(&self, index: &str, task_id: TaskId) -> Result<TaskStatus> {
+        self.task_status(index, task_id).await
+    }
+
+    /// Wait for a task to be published, polling its status with an increasing delay between checks.
+    ///
+    /// Gives up with `Error::TaskNotPublished` after the checks of `WaitTaskOptions::default`.
+    pub async fn wait_for_task(&self, index: &str, task_id: TaskId) -> Result<()> {
+        self.wait_for_tasks(index, &[task_id]).await
+    }
+
+    /// Same as `wait_for_task`, but returns the status of the published task,
+    /// e.g. to check that there are no other `pending_task`s on the index.
+    pub async fn wait_for_task_status(
+        &self,
+        index: &str,
+        task_id: TaskId,
+    ) -> Result<TaskStatusResponse> {
+        let resp = self
+            .wait_for_tasks_inner(index, &[task_id], WaitTaskOptions::default())
+            .await?;
+
+        Ok(resp.expect("the task was checked"))
+    }
+
+    /// Wait for all of `task_ids` to be published, e.g. the tasks returned by `batch_chunked`.
+    ///
+    /// The tasks share a single polling delay, and tasks that are already published aren't checked again.
+    pub async fn wait_for_tasks(&self, index: &str, task_ids: &[TaskId]) -> Result<()> {
+        self.wait_for_tasks_with(index, task_ids, WaitTaskOptions::default())
+            .await
+    }
+
+    /// `wait_for_tasks`, with control over how often and how long the tasks are polled.
+    ///
+    /// `TaskStatus::NotPublished` only means the task is still being processed, tasks don't fail
+    /// once they've been accepted, so this only errors when the tasks can't be checked or when
+    /// they're still not published after `options.max_retries` checks.
+    pub async fn wait_for_tasks_with(
+        &self,
+        index: &str,
+        task_ids: &[TaskId],
+        options: WaitTaskOptions,
+    ) -> Result<()> {
+        self.wait_for_tasks_inner(index, task_ids, options)
+            .await
+            .map(drop)
+    }
+
+    // returns the status of the last task to be published, `None` if there are no `task_ids`.
+    async fn wait_for_tasks_inner(
+        &self,
+        index: &str,
+        task_ids: &[TaskId],
+        options: WaitTaskOptions,
+    ) -> Result<Option<TaskStatusResponse>> {
+        let mut pending = task_ids.to_vec();
+        let mut delay = options.initial_delay;
+        let mut checks = 0;
+        let mut last_published = None;
+
+        loop {
+            let mut still_pending = Vec::with_capacity(pending.len());
+
+            for task_id in pending {
+                let resp = self.task(index, task_id).await?;
+                if resp.status.completed() {
+                    last_published = Some(resp);
+                } else {
+                    still_pending.push(task_id);
+                }
+            }
+
+            checks += 1;
+
+            if still_pending.is_empty() {
+                return Ok(last_published);
+            }
+
+            if checks >= options.max_retries {
+                return Err(Error::TaskNotPublished {
+                    index: index.to_owned(),
+                    task_id: still_pending[0],
+                    checks,
+                });
+            }
+
+            pending = still_pending;
+
+            tokio::time::sleep(delay).await;
+            delay = (delay * 2).min(options.max_delay);
+        }
+    }
+
+    /// Search an index.
+    ///
+    /// `V` is the type of the records, and `F` the type the facet counts are deserialized into
+    /// (see `SearchResponse::facets`), which can usually be inferred from the default.
+    #[inline(always)]
+    pub async fn search<
+        T: CommonFilterKind,
+        U: Filterable,
+        V: DeserializeOwned,
+        F: DeserializeOwned,
+    >(
+        &self,
+        index: &str,
+        request: SearchQuery<'_, T, U>,
+    ) -> Result<SearchResponse<V, F>> {
+        debug_assert!(
+            !request.sum_or_filters_scores || request.has_scored_filters(),
+            "`sum_or_filters_scores` has no effect without `optional_filters` or scored filters"
+        );
+
+        let optional_filters = request
+            .optional_filters
+            .as_deref()
+            .unwrap_or_default()
+            .iter()
+            .map(|it: &CommonFilter<T>| format!("{}", it))
+            .collect::<Vec<_>>();
+
+        let user_token = request.user_token;
+
+        let request = request.to_params_string();
+        let request = &*request;
+
+        self.search_inner(index, request, &optional_filters, user_token)
+            .await
+    }
+
+    /// Search with an already encoded `params` string, e.g. one captured from an InstantSearch frontend,
+    /// for parameters that `SearchQuery` doesn't support (yet).
+    ///
+    /// `params` is sent as-is, only the client's `with_user_token` applies.
+    pub async fn search_raw_params<T: DeserializeOwned, F: DeserializeOwned>(
+        &self,
+        index: &str,
+        params: &str,
+    ) -> Result<SearchResponse<T, F>> {
+        self.search_inner::<T, F, &str>(index, params, &[], None)
+            .await
+    }
+
+    // Wrapped by `search`. But removes of the generic arguments
+    // to avoid more instantiations of this function than needed.
+    async fn search_inner<T: DeserializeOwned, F: DeserializeOwned, U: AsRef<str>>(
+        &self,
+        index: &str,
+        request: &str,
+        optional_filters: &[U],
+        user_token: Option<&str>,
+    ) -> Result<SearchResponse<T, F>> {
+        #[derive(serde::Serialize)]
+        #[serde(rename_all = "camelCase")]
+        struct Request<'a> {
+            params: &'a str,
+            optional_filters: &'a [&'a str],
+        }
+
+        let optional_filters = &optional_filters
+            .iter()
+            .map(|it| it.as_ref())
+            .collect::<Vec<_>>();
+        let start = Instant::now();
+
+        let resp: SearchResponse<T, F> = self
+            .retry_with(
+                IndexRoute {
+                    index_name: index,
+                    kind: Some(IndexRouteKind::Query),
+                },
+                // every attempt borrows `request` and `optional_filters` again, and rebuilds the same body.
+                |url| async move {
+                    let mut req = self.search_request(&url, user_token);
+
+                    req = req.json(&Request {
+                        params: request,
+                        optional_filters,
+                    });
+
+                    let resp = unwrap_ret!(check_response(req.send().await, Some(index)).await);
+
+                    decode(resp).await
+                },
+            )
+            .await?;
+
+        if let Some(stats) = &self.search_stats {
+            stats.record(resp.processing_time_ms, start.elapsed());
+        }
+
+        Ok(resp)
+    }
+
+    fn search_request(&self, url: &str, user_token: Option<&str>) -> reqwest::RequestBuilder {
+        let req = self.request(Method::POST, url);
+
+        match user_token.or(self.user_token.as_deref()) {
+            Some(user_token) => req.header("X-Algolia-UserToken", user_token),
+            None => req,
+        }
+    }
+
+    /// Add an object, letting algolia generate its object ID.
+    ///
+    /// Since sending it twice would create two objects, this request isn't retried on another host after a timeout,
+    /// in which case the object may or may not have been created.
+    /// Prefer `add_or_update_object` when the object has a natural ID.
+    pub async fn add_object<T: serde::Serialize>(
+        &self,
+        index: &str,
+        body: &T,
+    ) -> Result<ObjectCreateResponse> {
+        self.retry_with(
+            WithIdempotency {
+                route: IndexRoute {
+                    index_name: index,
+                    kind: None,
+                },
+                idempotent: false,
+            },
+            |url| async move {
+                let resp = unwrap_ret!(
+                    check_response(
+                        self.request(Method::POST, &url).json(body).send().await,
+                        None
+                    )
+                    .await
+                );
+
+                decode(resp).await
+            },
+        )
+        .await
+    }
+
+    /// Retrieve a single object, `None` if it (or the index) doesn't exist.
+    pub async fn get_object<T: DeserializeOwned>(
+        &self,
+        index: &str,
+        object_id: &str,
+    ) -> Result<Option<T>> {
+        let object = self
+            .get_object_if_none_match(index, object_id, None)
+            .await?;
+
+        Ok(object.and_then(|it| match it {
+            Conditional::Modified { object, .. } => Some(object),
+            // can't happen without an `etag`.
+            Conditional::NotModified => None,
+        }))
+    }
+
+    /// Retrieve a single object, unless it still matches `etag` (from a previous `Conditional::Modified`),
+    /// in which case `Conditional::NotModified` is returned without downloading the object again.
+    ///
+    /// Returns `None` if the object (or the index) doesn't exist.
+    pub async fn get_object_if_none_match<T: DeserializeOwned>(
+        &self,
+        index: &str,
+        object_id: &str,
+        etag: Option<&str>,
+    ) -> Result<Option<Conditional<T>>> {
+        self.retry_with(
+            ObjectRoute {
+                index_name: index,
+                object_id,
+                partial: false,
+            },
+            |url| async move {
+                let mut req = self.request(Method::GET, &url);
+
+                if let Some(etag) = etag {
+                    req = req.header(IF_NONE_MATCH, etag);
+                }
+
+                let resp = req.send().await;
+
+                // `check_response` would make an error out of a 404.
+                if matches!(&resp, Ok(resp) if resp.status() == StatusCode::NOT_FOUND) {
+                    return Ok(Some(None));
+                }
+
+                let resp = unwrap_ret!(check_response(resp, None).await);
+
+                // a 304 has an empty body, there's nothing to decode.
+                if resp.status() == StatusCode::NOT_MODIFIED {
+                    return Ok(Some(Some(Conditional::NotModified)));
+                }
+
+                let etag = resp
+                    .headers()
+                    .get(ETAG)
+                    .and_then(|it| it.to_str().ok())
+                    .map(str::to_owned);
+
+                let object = unwrap_ret!(decode(resp).await);
+
+                Ok(Some(Some(Conditional::Modified { object, etag })))
+            },
+        )
+        .await
+    }
+
+    /// Add or replace an object with a given object ID.
+    /// If the object does not exist, it will be created. If it already exists, it will be replaced.
+    pub async fn add_or_update_object<T: serde::Serialize>(
+        &self,
+        index: &str,
+        object_id: &str,
+        body: &T,
+    ) -> Result<ObjectUpdateResponse> {
+        self.retry_with(
+            ObjectRoute {
+                index_name: index,
+                object_id,
+                partial: false,
+            },
+            |url| async move {
+                let resp = unwrap_ret!(
+                    check_response(
+                        self.request(Method::PUT, &url).json(body).send().await,
+                        None
+                    )
+                    .await
+                );
+
+                decode(resp).await
+            },
+        )
+            .await
+    }
+
+    /// Partially update many objects, e.g. to decrement the stock of a lot of products,
+    /// in batches of `Client::DEFAULT_BATCH_CHUNK_SIZE`.
+    ///
+    /// When `create_if_not_exists` is false, updates of objects that don't exist are ignored.
+    pub async fn partial_update_objects(
+        &self,
+        index: &str,
+        updates: impl IntoIterator<Item = (String, PartialUpdateBody)>,
+        create_if_not_exists: bool,
+    ) -> Result<Vec<BatchWriteResponse>> {
+        let requests = updates
+            .into_iter()
+            .map(|(object_id, body)| body.into_batch_request(object_id, create_if_not_exists))
+            .collect();
+
+        self.batch_chunked(index, requests, Self::DEFAULT_BATCH_CHUNK_SIZE, None)
+            .await
+    }
+
+    /// Partially update an object.
+    ///
+    /// This creates a brand new record if it doesn’t already exist (and the createIfNotExists parameter isn’t set to false).
+    ///
+    /// You can pass any first-level attribute you want to add or replace within the record, but you can’t individually update nested attributes.
+    /// If you specify a nested attribute, the engine treats it as a replacement of its first-level ancestor.
+    pub async fn partially_update_object<T: serde::Serialize>(
+        &self,
+        index: &str,
+        object_id: &str,
+        body: &T,
+        query: &PartialUpdateQuery,
+    ) -> Result<ObjectUpdateResponse> {
+        self.retry_with(
+            ObjectRoute {
+                index_name: index,
+                object_id,
+                partial: true,
+            },
+            |url| async move {
+                let resp = unwrap_ret!(
+                    check_response(
+                        self.request(Method::POST, &url)
+                            .query(query)
+                            .json(body)
+                            .send()
+                            .await,
+                        None
+                    )
+                    .await
+                );
+
+                decode(resp).await
+            },
+        )
+            .await
+    }
+
+    /// Partially update an object, only if it already exists.
+    ///
+    /// Same as `partially_update_object` with `create_if_not_exists` set to `false`, the batch equivalent being `PartialUpdateObjectNoCreate`.
+    /// Updating a missing object isn't an error, it's a no-op that still returns a task.
+    pub async fn partially_update_object_no_create<T: serde::Serialize>(
+        &self,
+        index: &str,
+        object_id: &str,
+        body: &T,
+    ) -> Result<ObjectUpdateResponse> {
+        self.partially_update_object(
+            index,
+            object_id,
+            body,
+            &PartialUpdateQuery {
+                create_if_not_exists: false,
+            },
+        )
+        .await
+    }
+
+    /// Delete an existing object from an index.
+    pub async fn delete_object(
+        &self,
+        index: &str,
+        object_id: &str,
+    ) -> Result<ObjectDeleteResponse> {
+        self.retry_with(
+            ObjectRoute {
+                index_name: index,
+                object_id,
+                partial: false,
+            },
+            |url| async move {
+                let resp = unwrap_ret!(
+                    check_response(self.request(Method::DELETE, &url).send().await, None).await
+                );
+
+                decode(resp).await
+            },
+        )
+            .await
+    }
+
+    /// Retrieve several objects, possibly from different indices, in a single call.
+    ///
+    /// Objects are returned in the same order as `requests`, `None` means that the object wasn't found.
+    pub async fn get_objects<T: DeserializeOwned>(
+        &self,
+        requests: &[GetObjectRequest],
+    ) -> Result<Vec<Option<T>>> {
+        self.get_objects_inner::<Option<T>>(requests)
+            .await
+            .map(|it| it.results)
+    }
+
+    /// Same as `get_objects`, but leaves the objects as raw JSON (`Value::Null` for objects that weren't found).
+    ///
+    /// This is useful when the objects don't share a single type, e.g. when they come from differently-shaped indices.
+    ///
+    /// # Examples
+    /// ```no_run
+    /// # async fn run(client: algolia::Client) -> algolia::Result<()> {
+    /// use algolia::request::GetObjectRequest;
+    ///
+    /// #[derive(serde::Deserialize)]
+    /// struct Product { name: String }
+    ///
+    /// #[derive(serde::Deserialize)]
+    /// struct User { email: String }
+    ///
+    /// let mut objects = client
+    ///     .get_objects_raw(&[
+    ///         GetObjectRequest::new("products".to_owned(), "1".to_owned()),
+    ///         GetObjectRequest::new("users".to_owned(), "42".to_owned()),
+    ///     ])
+    ///     .await?
+    ///     .into_iter();
+    ///
+    /// let product: Option<Product> = serde_json::from_value(objects.next().unwrap()).unwrap();
+    /// let user: Option<User> = serde_json::from_value(objects.next().unwrap()).unwrap();
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn get_objects_raw(
+        &self,
+        requests: &[GetObjectRequest],
+    ) -> Result<Vec<serde_json::Value>> {
+        self.get_objects_inner::<serde_json::Value>(requests)
+            .await
+            .map(|it| it.results)
+    }
+
+    async fn get_objects_inner<T: DeserializeOwned>(
+        &self,
+        requests: &[GetObjectRequest],
+    ) -> Result<GetObjectsResponse<T>> {
+        #[derive(serde::Serialize)]
+        struct Request<'a> {
+            requests: &'a [GetObjectRequest],
+        }
+
+        let body = &Request { requests };
+
+        self.retry_with(
+            IndexRoute {
+                index_name: "*",
+                kind: Some(IndexRouteKind::Objects),
+            },
+            |url| async move {
+                let resp = unwrap_ret!(
+                    check_response(
+                        self.request(Method::POST, &url).json(body).send().await,
+                        None
+                    )
+                    .await
+                );
+
+                decode(resp).await
+            },
+        )
+        .await
+    }
+
+    /// Get recommendations from the Recommend API, one response per request, in order.
+    pub async fn get_recommendations<T: DeserializeOwned>(
+        &self,
+        requests: &[RecommendationsQuery],
+    ) -> Result<Vec<RecommendResponse<T>>> {
+        #[derive(serde::Serialize)]
+        struct Request<'a> {
+            requests: &'a [RecommendationsQuery],
+        }
+
+        #[derive(serde::Deserialize)]
+        struct Response<T> {
+            results: Vec<RecommendResponse<T>>,
+        }
+
+        let body = &Request { requests };
+
+        let resp: Response<T> = self
+            .retry_with(
+                IndexRoute {
+                    index_name: "*",
+                    kind: Some(IndexRouteKind::Recommendations),
+                },
+                |url| async move {
+                    let resp = unwrap_ret!(
+                        check_response(
+                            self.request(Method::POST, &url).json(body).send().await,
+                            None
+                        )
+                        .await
+                    );
+
+                    decode(resp).await
+                },
+            )
+            .await?;
+
+        Ok(resp.results)
+    }
+
+    // Every synonym/rule write goes through here, so that they all handle `forwardToReplicas` the same way.
+    async fn replica_write<T: Route, B: serde::Serialize + ?Sized, O: DeserializeOwned>(
+        &self,
+        method: Method,
+        route: T,
+        body: Option<&B>,
+        query: ReplicaWriteQuery,
+    ) -> Result<O> {
+        let query = &query;
+
+        self.retry_with(route, |url| {
+            let method = method.clone();
+
+            async move {
+                let mut req = self.request(method, &url).query(query);
+
+                if let Some(body) = body {
+                    req = req.json(body);
+                }
+
+                let resp = unwrap_ret!(check_response(req.send().await, None).await);
+
+                decode(resp).await
+            }
+        })
+        .await
+    }
+
+    /// Create or replace a single synonym.
+    pub async fn save_synonym(
+        &self,
+        index: &str,
+        synonym: &Synonym,
+        forward_to_replicas: bool,
+    ) -> Result<SynonymsUpdateResponse> {
+        self.replica_write(
+            Method::PUT,
+            DictionaryEntryRoute {
+                index_name: index,
+                dictionary: Dictionary::Synonyms,
+                object_id: synonym.object_id(),
+            },
+            Some(synonym),
+            ReplicaWriteQuery::new(forward_to_replicas),
+        )
+        .await
+    }
+
+    /// Create or update multiple synonyms at once.
+    ///
+    /// When `replace_existing_synonyms` is set, every synonym not in `synonyms` is removed.
+    pub async fn batch_synonyms(
+        &self,
+        index: &str,
+        synonyms: &[Synonym],
+        forward_to_replicas: bool,
+        replace_existing_synonyms: bool,
+    ) -> Result<SynonymsUpdateResponse> {
+        self.replica_write(
+            Method::POST,
+            IndexRoute {
+                index_name: index,
+                kind: Some(IndexRouteKind::SynonymsBatch),
+            },
+            Some(synonyms),
+            ReplicaWriteQuery {
+                replace_existing_synonyms,
+                ..ReplicaWriteQuery::new(forward_to_replicas)
+            },
+        )
+        .await
+    }
+
+    /// Delete a single synonym.
+    pub async fn delete_synonym(
+        &self,
+        index: &str,
+        object_id: &str,
+        forward_to_replicas: bool,
+    ) -> Result<SynonymDeleteResponse> {
+        self.replica_write(
+            Method::DELETE,
+            DictionaryEntryRoute {
+                index_name: index,
+                dictionary: Dictionary::Synonyms,
+                object_id,
+            },
+            None::<&()>,
+            ReplicaWriteQuery::new(forward_to_replicas),
+        )
+        .await
+    }
+
+    /// Remove all synonyms from an index.
+    pub async fn clear_synonyms(
+        &self,
+        index: &str,
+        forward_to_replicas: bool,
+    ) -> Result<SynonymsUpdateResponse> {
+        self.replica_write(
+            Method::POST,
+            IndexRoute {
+                index_name: index,
+                kind: Some(IndexRouteKind::SynonymsClear),
+            },
+            None::<&()>,
+            ReplicaWriteQuery::new(forward_to_replicas),
+        )
+        .await
+    }
+
+    /// Replace the whole synonyms dictionary of an index (and its replicas) with `synonyms`,
+    /// then wait for the change to be published.
+    ///
+    /// This is the synonyms equivalent of replacing all objects, an empty `synonyms` clears the dictionary.
+    pub async fn replace_all_synonyms(
+        &self,
+        index: &str,
+        synonyms: &[Synonym],
+    ) -> Result<SynonymsUpdateResponse> {
+        let resp = if synonyms.is_empty() {
+            self.clear_synonyms(index, true).await?
+        } else {
+            self.batch_synonyms(index, synonyms, true, true).await?
+        };
+
+        self.wait_for_task(index, resp.task_id).await?;
+
+        Ok(resp)
+    }
+
+    /// Create or replace a single Query Rule.
+    pub async fn save_rule(
+        &self,
+        index: &str,
+        rule: &Rule,
+        forward_to_replicas: bool,
+    ) -> Result<RulesUpdateResponse> {
+        self.replica_write(
+            Method::PUT,
+            DictionaryEntryRoute {
+                index_name: index,
+                dictionary: Dictionary::Rules,
+                object_id: &rule.object_id,
+            },
+            Some(rule),
+            ReplicaWriteQuery::new(forward_to_replicas),
+        )
+        .await
+    }
+
+    /// Create or update multiple Query Rules at once.
+    ///
+    /// When `clear_existing_rules` is set, every rule not in `rules` is removed.
+    pub async fn batch_rules(
+        &self,
+        index: &str,
+        rules: &[Rule],
+        forward_to_replicas: bool,
+        clear_existing_rules: bool,
+    ) -> Result<RulesUpdateResponse> {
+        self.replica_write(
+            Method::POST,
+            IndexRoute {
+                index_name: index,
+                kind: Some(IndexRouteKind::RulesBatch),
+            },
+            Some(rules),
+            ReplicaWriteQuery {
+                clear_existing_rules,
+                ..ReplicaWriteQuery::new(forward_to_replicas)
+            },
+        )
+        .await
+    }
+
+    /// Delete a single Query Rule.
+    pub async fn delete_rule(
+        &self,
+        index: &str,
+        object_id: &str,
+        forward_to_replicas: bool,
+    ) -> Result<RuleDeleteResponse> {
+        self.replica_write(
+            Method::DELETE,
+            DictionaryEntryRoute {
+                index_name: index,
+                dictionary: Dictionary::Rules,
+                object_id,
+            },
+            None::<&()>,
+            ReplicaWriteQuery::new(forward_to_replicas),
+        )
+        .await
+    }
+
+    /// Remove all Query Rules from an index.
+    pub async fn clear_rules(
+        &self,
+        index: &str,
+        forward_to_replicas: bool,
+    ) -> Result<RulesUpdateResponse> {
+        self.replica_write(
+            Method::POST,
+            IndexRoute {
+                index_name: index,
+                kind: Some(IndexRouteKind::RulesClear),
+            },
+            None::<&()>,
+            ReplicaWriteQuery::new(forward_to_replicas),
+        )
+        .await
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{chunk_batch, Client, IndexRoute, IndexRouteKind, WithIdempotency};
+    use crate::{
+        filter::{CommonFilter, FacetFilter},
+        insights::{EventType, InsightsEvent},
+        model::task::{TaskId, TaskStatus, WaitTaskOptions},
+        recommend::{RecommendModel, RecommendationsQuery},
+        request::{BatchWriteRequest, BatchWriteRequests, SearchQuery, SetSettings},
+        response::{Conditional, SearchResponse},
+        stats::SearchStats,
+        ApiKey, AppId, Error,
+    };
+    use reqwest::{
+        header::{HeaderMap, HeaderValue},
+        Method,
+    };
+    use std::{
+        io::{BufRead, BufReader, Read, Write},
+        net::TcpListener,
+        sync::{
+            atomic::{AtomicUsize, Ordering},
+            Arc, Mutex,
+        },
+        time::Duration,
+    };
+
+    fn client() -> Client {
+        Client::new(AppId::new("test".to_owned()), ApiKey("key".to_owned())).unwrap()
+    }
+
+    // what the mock server received, in order.
+    type Received = Arc<Mutex<Vec<String>>>;
+
+    // same as `mock_with_bodies`, without the bodies.
+    fn mock(responses: Vec<(u16, &'static str)>) -> (Client, Received) {
+        let (client, requests, _) = mock_with_bodies(responses);
+        (client, requests)
+    }
+
+    // same as `mock_server`, without extra response headers nor the received headers.
+    fn mock_with_bodies(responses: Vec<(u16, &'static str)>) -> (Client, Received, Received) {
+        let responses = responses
+            .into_iter()
+            .map(|(status, body)| (status, "", body))
+            .collect();
+
+        let (client, requests, _, bodies) = mock_server(responses);
+        (client, requests, bodies)
+    }
+
+    /// A server answering each connection with the next of `responses`, as `(status, headers, JSON body)`
+    /// where `headers` are extra `name: value\r\n` lines.
+    /// Returns a client pointed at it, and what it received: the `METHOD /path` of the requests,
+    /// their header lines, and their bodies.
+    fn mock_server(
+        responses: Vec<(u16, &'static str, &'static str)>,
+    ) -> (Client, Received, Received, Received) {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let base_url = format!("http://{}", listener.local_addr().unwrap());
+
+        let requests = Arc::new(Mutex::new(Vec::new()));
+        let received = requests.clone();
+
+        let headers = Arc::new(Mutex::new(Vec::new()));
+        let received_headers = headers.clone();
+
+        let bodies = Arc::new(Mutex::new(Vec::new()));
+        let received_bodies = bodies.clone();
+
+        std::thread::spawn(move || {
+            for (status, response_headers, body) in responses {
+                let (stream, _) = listener.accept().unwrap();
+                let mut reader = BufReader::new(stream);
+
+                let mut request_line = String::new();
+                reader.read_line(&mut request_line).unwrap();
+
+                let mut content_length = 0;
+                let mut request_headers = String::new();
+                loop {
+                    let mut header = String::new();
+                    reader.read_line(&mut header).unwrap();
+                    if header.trim().is_empty() {
+                        break;
+                    }
+
+                    request_headers.push_str(&header);
+
+                    if let Some((name, value)) = header.split_once(':') {
+                        if name.eq_ignore_ascii_case("content-length") {
+                            content_length = value.trim().parse().unwrap();
+                        }
+                    }
+                }
+
+                let mut request_body = vec![0; content_length];
+                reader.read_exact(&mut request_body).unwrap();
+
+                let path = request_line.rsplit_once(' ').unwrap().0;
+                received.lock().unwrap().push(path.to_owned());
+                received_headers.lock().unwrap().push(request_headers);
+                received_bodies
+                    .lock()
+                    .unwrap()
+                    .push(String::from_utf8(request_body).unwrap());
+
+                write!(
+                    reader.into_inner(),
+                    "HTTP/1.1 {} Mock\r\ncontent-type: application/json\r\ncontent-length: {}\r\nconnection: close\r\n{}\r\n{}",
+                    status,
+                    body.len(),
+                    response_headers,
+                    body
+                )
+                .unwrap();
+            }
+        });
+
+        (client().with_base_url(base_url), requests, headers, bodies)
+    }
+
+    // every attempt "times out", and counts how many hosts were tried.
+    async fn attempts(idempotent: bool) -> usize {
+        let attempts = AtomicUsize::new(0);
+        let attempts_ref = &attempts;
+
+        let res = client()
+            .retry_with(
+                WithIdempotency {
+                    route: IndexRoute {
+                        index_name: "test",
+                        kind: None,
+                    },
+                    idempotent,
+                },
+                |_| async move {
+                    attempts_ref.fetch_add(1, Ordering::SeqCst);
+                    Ok(None::<()>)
+                },
+            )
+            .await;
+
+        let attempts = attempts.load(Ordering::SeqCst);
+
+        match res {
+            Err(Error::Timeout {
+                route,
+                attempts: reported,
+            }) => {
+                assert_eq!(route, "indexes/test");
+                assert_eq!(reported, attempts);
+            }
+            res => panic!("expected a timeout, got {:?}", res),
+        }
+
+        attempts
+    }
+
+    #[tokio::test]
+    async fn idempotent_requests_try_every_host() {
+        assert_eq!(attempts(true).await, 1 + crate::HOST_FALLBACK_LIST.len());
+    }
+
+    #[tokio::test]
+    async fn non_idempotent_requests_are_not_retried() {
+        assert_eq!(attempts(false).await, 1);
+    }
+
+    // the hosts tried for `kind`, the fallbacks (after the first) being shuffled.
+    async fn hosts(kind: Option<IndexRouteKind>) -> Vec<String> {
+        let urls = Mutex::new(Vec::new());
+        let urls_ref = &urls;
+
+        let _ = client()
+            .retry_with(
+                IndexRoute {
+                    index_name: "test",
+                    kind,
+                },
+                |url| async move {
+                    urls_ref.lock().unwrap().push(url);
+                    Ok(None::<()>)
+                },
+            )
+            .await;
+
+        urls.into_inner().unwrap()
+    }
+
+    #[tokio::test]
+    async fn reads_try_the_dsn_host_first() {
+        let urls = hosts(Some(IndexRouteKind::Query)).await;
+        assert_eq!(urls[0], "https://test-dsn.algolia.net/1/indexes/test/query");
+        assert!(urls[1..].iter().all(|it| !it.contains("-dsn")));
+
+        let urls = hosts(None).await;
+        assert_eq!(urls[0], "https://test.algolia.net/1/indexes/test");
+        assert_eq!(urls.len(), 1 + crate::HOST_FALLBACK_LIST.len());
+    }
+
+    #[tokio::test]
+    async fn base_url() {
+        let (client, requests) = mock(vec![(500, "{}"), (200, r#"{"hitsPerPage":20}"#)]);
+
+        let settings = client.get_settings("products").await.unwrap();
+        assert_eq!(settings.get("hitsPerPage"), Some(&serde_json::json!(20)));
+
+        // the server error is retried.
+        assert_eq!(
+            *requests.lock().unwrap(),
+            [
+                "GET /1/indexes/products/settings",
+                "GET /1/indexes/products/settings"
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn get_object_etag() {
+        let (client, requests, headers, _) = mock_server(vec![
+            (
+                200,
+                "etag: \"v1\"\r\n",
+                r#"{"objectID":"a","name":"first"}"#,
+            ),
+            (304, "", ""),
+            (
+                404,
+                "",
+                r#"{"message":"ObjectID does not exist","status":404}"#,
+            ),
+        ]);
+
+        let object = client
+            .get_object_if_none_match::<serde_json::Value>("products", "a", None)
+            .await
+            .unwrap();
+
+        assert_eq!(
+            object,
+            Some(Conditional::Modified {
+                object: serde_json::json!({ "objectID": "a", "name": "first" }),
+                etag: Some(r#""v1""#.to_owned()),
+            })
+        );
+
+        let object = client
+            .get_object_if_none_match::<serde_json::Value>("products", "a", Some(r#""v1""#))
+            .await
+            .unwrap();
+
+        assert_eq!(object, Some(Conditional::NotModified));
+
+        let object = client
+            .get_object::<serde_json::Value>("products", "b")
+            .await
+            .unwrap();
+
+        assert_eq!(object, None);
+
+        assert_eq!(
+            *requests.lock().unwrap(),
+            [
+                "GET /1/indexes/products/a",
+                "GET /1/indexes/products/a",
+                "GET /1/indexes/products/b",
+            ]
+        );
+
+        let headers = headers.lock().unwrap();
+        let if_none_match = |headers: &str| {
+            headers
+                .lines()
+                .find_map(|it| it.strip_prefix("if-none-match: "))
+                .map(str::to_owned)
+        };
+
+        assert_eq!(if_none_match(&headers[0]), None);
+        assert_eq!(if_none_match(&headers[1]), Some(r#""v1""#.to_owned()));
+    }
+
+    #[tokio::test]
+    async fn get_recommendations() {
+        let (client, requests, bodies) = mock_with_bodies(vec![(
+            200,
+            r#"{"results":[{"hits":[{"objectID":"b","_score":32.72,"name":"socks"}],"processingTimeMS":1}]}"#,
+        )]);
+
+        let query = RecommendationsQuery::new(
+            "products".to_owned(),
+            RecommendModel::RelatedProducts,
+            "a".to_owned(),
+        );
+
+        let results = client
+            .get_recommendations::<serde_json::Value>(&[query])
+            .await
+            .unwrap();
+
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].hits[0].object_id, "b");
+        assert_eq!(results[0].hits[0].score, Some(32.72));
+        assert_eq!(results[0].hits[0].inner["name"], "socks");
+
+        assert_eq!(
+            *requests.lock().unwrap(),
+            ["POST /1/indexes/*/recommendations"]
+        );
+        assert_eq!(
+            serde_json::from_str::<serde_json::Value>(&bodies.lock().unwrap()[0]).unwrap(),
+            serde_json::json!({
+                "requests": [{
+                    "indexName": "products",
+                    "model": "related-products",
+                    "objectID": "a",
+                    "threshold": 0,
+                }]
+            })
+        );
+    }
+
+    #[tokio::test]
+    async fn search_retry_resends_body() {
+        // with a base url, the "fallback host" is the same server.
+        let (client, requests, bodies) = mock_with_bodies(vec![
+            (500, "{}"),
+            (
+                200,
+                r#"{"hits":[],"page":0,"nbHits":0,"nbPages":0,"hitsPerPage":20,"processingTimeMS":1,"query":"shoes","params":"query=shoes"}"#,
+            ),
+        ]);
+
+        let query = SearchQuery::<String> {
+            query: Some("shoes"),
+            optional_filters: Some(vec![CommonFilter {
+                invert: false,
+                filter: r#""brand":"acme""#.to_owned(),
+            }]),
+            ..SearchQuery::default()
+        };
+
+        let _: SearchResponse = client.search("products", query).await.unwrap();
+
+        assert_eq!(requests.lock().unwrap().len(), 2);
+
+        let bodies = bodies.lock().unwrap();
+        assert_eq!(bodies[0], bodies[1]);
+        assert_eq!(
+            serde_json::from_str::<serde_json::Value>(&bodies[0]).unwrap(),
+            serde_json::json!({
+                "params": "query=shoes",
+                "optionalFilters": [r#""brand":"acme""#],
+            })
+        );
+    }
+
+    #[tokio::test]
+    async fn search_all_filters() {
+        let (client, _, _, bodies) = mock_server(vec![(
+            200,
+            "",
+            r#"{"hits":[],"page":0,"nbHits":0,"nbPages":0,"hitsPerPage":20,"processingTimeMS":1,"query":"","params":""}"#,
+        )]);
+
+        let query = SearchQuery::<String, _> {
+            filters: Some(CommonFilter {
+                invert: false,
+                filter: FacetFilter {
+                    facet_name: "brand".to_owned(),
+                    value: "acme".to_owned(),
+                },
+            }),
+            facet_filters: Some(vec![vec!["color:red".to_owned()]]),
+            numeric_filters: Some(vec![vec!["price<100".to_owned()]]),
+            tag_filters: Some(vec![vec!["new".to_owned()]]),
+            ..SearchQuery::default()
+        };
+
+        let _: SearchResponse = client.search("products", query).await.unwrap();
+
+        let body: serde_json::Value = serde_json::from_str(&bodies.lock().unwrap()[0]).unwrap();
+        let params =
+            serde_urlencoded::from_str::<Vec<(String, String)>>(body["params"].as_str().unwrap())
+                .unwrap();
+
+        assert_eq!(
+            params,
+            [
+                ("filters".to_owned(), r#""brand":"acme""#.to_owned()),
+                ("facetFilters".to_owned(), r#"[["color:red"]]"#.to_owned()),
+                ("numericFilters".to_owned(), r#"[["price<100"]]"#.to_owned()),
+                ("tagFilters".to_owned(), r#"[["new"]]"#.to_owned()),
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn search_raw_params() {
+        let (client, requests) = mock(vec![(
+            200,
+            r#"{"hits":[{"objectID":"a"}],"page":0,"nbHits":1,"nbPages":1,"hitsPerPage":20,"processingTimeMS":1,"query":"shoes","params":"query=shoes&page=0"}"#,
+        )]);
+
+        let resp: SearchResponse = client
+            .search_raw_params("products", "query=shoes&page=0")
+            .await
+            .unwrap();
+
+        assert_eq!(resp.hits[0].object_id, "a");
+        assert_eq!(resp.params, "query=shoes&page=0");
+        assert_eq!(
+            *requests.lock().unwrap(),
+            ["POST /1/indexes/products/query"]
+        );
+    }
+
+    #[tokio::test]
+    async fn timeout() {
+        // accepts the connections, but never responds.
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let base_url = format!("http://{}", listener.local_addr().unwrap());
+
+        std::thread::spawn(move || {
+            let _streams: Vec<_> = listener.incoming().collect();
+        });
+
+        let client = client().with_base_url(base_url);
+
+        let requests = BatchWriteRequests {
+            requests: vec![BatchWriteRequest::DeleteObject {
+                object_id: "a".to_owned(),
+            }],
+        };
+
+        let res = client
+            .batch("products", &requests, Some(Duration::from_millis(50)))
+            .await;
+
+        match res {
+            Err(Error::Timeout { attempts, .. }) => {
+                assert_eq!(attempts, 1 + crate::HOST_FALLBACK_LIST.len())
+            }
+            res => panic!("expected a timeout, got {:?}", res),
+        }
+    }
+
+    #[tokio::test]
+    async fn search_stats() {
+        let (client, _) = mock(vec![(
+            200,
+            r#"{"hits":[],"page":0,"nbHits":0,"nbPages":0,"hitsPerPage":20,"processingTimeMS":3,"query":"","params":""}"#,
+        )]);
+
+        let stats = SearchStats::new();
+        let client = client.with_search_stats(stats.clone());
+
+        let _: SearchResponse = client.search_raw_params("products", "").await.unwrap();
+
+        let totals = stats.totals();
+        assert_eq!(totals.searches, 1);
+        assert_eq!(totals.processing_time, Duration::from_millis(3));
+        assert_eq!(totals.max_elapsed, totals.elapsed);
+    }
+
+    #[tokio::test]
+    async fn ensure_index() {
+        let (client, requests) = mock(vec![
+            (404, r#"{"message":"Index does not exist","status":404}"#),
+            (
+                200,
+                r#"{"updatedAt":"2021-08-06T12:00:00.000Z","taskID":42}"#,
+            ),
+            (200, r#"{"status":"published","pendingTask":false}"#),
+        ]);
+
+        assert!(!client.index_exists("products").await.unwrap());
+
+        client
+            .ensure_index("products", &SetSettings::patch())
+            .await
+            .unwrap();
+
+        assert_eq!(
+            *requests.lock().unwrap(),
+            [
+                "GET /1/indexes/products/settings",
+                "PUT /1/indexes/products/settings",
+                "GET /1/indexes/products/task/42",
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn wait_for_task() {
+        let (client, requests) = mock(vec![
+            (200, r#"{"status":"notPublished","pendingTask":true}"#),
+            (200, r#"{"status":"notPublished","pendingTask":true}"#),
+            (200, r#"{"status":"published","pendingTask":false}"#),
+        ]);
+
+        let options = WaitTaskOptions {
+            initial_delay: Duration::from_millis(1),
+            ..WaitTaskOptions::default()
+        };
+
+        client
+            .wait_for_tasks_with("products", &[TaskId(42)], options)
+            .await
+            .unwrap();
+
+        assert_eq!(requests.lock().unwrap().len(), 3);
+    }
+
+    #[tokio::test]
+    async fn wait_for_task_status() {
+        let (client, _) = mock(vec![
+            (200, r#"{"status":"notPublished","pendingTask":true}"#),
+            (200, r#"{"status":"published","pendingTask":true}"#),
+        ]);
+
+        let resp = client
+            .wait_for_task_status("products", TaskId(42))
+            .await
+            .unwrap();
+
+        assert_eq!(resp.status, TaskStatus::Published);
+        assert!(resp.pending_task);
+    }
+
+    #[tokio::test]
+    async fn wait_for_task_gives_up() {
+        let (client, requests) = mock(vec![
+            (200, r#"{"status":"notPublished","pendingTask":true}"#),
+            (200, r#"{"status":"notPublished","pendingTask":true}"#),
+        ]);
+
+        let options = WaitTaskOptions {
+            max_retries: 2,
+            initial_delay: Duration::from_millis(1),
+            ..WaitTaskOptions::default()
+        };
+
+        let err = client
+            .wait_for_tasks_with("products", &[TaskId(42)], options)
+            .await
+            .unwrap_err();
+
+        assert!(matches!(
+            err,
+            Error::TaskNotPublished {
+                task_id: TaskId(42),
+                checks: 2,
+                ..
+            }
+        ));
+        assert_eq!(requests.lock().unwrap().len(), 2);
+    }
+
+    #[tokio::test]
+    async fn export_ndjson() {
+        let (client, requests) = mock(vec![
+            (
+                200,
+                r#"{"hits":[{"objectID":"a","name":"first"}],"cursor":"next","nbHits":2,"processingTimeMS":1}"#,
+            ),
+            (
+                200,
+                r#"{"hits":[{"objectID":"b","name":"second"}],"nbHits":2,"processingTimeMS":1}"#,
+            ),
+        ]);
+
+        let mut out = Vec::new();
+        let count = client.export_ndjson("products", &mut out).await.unwrap();
+
+        assert_eq!(count, 2);
+        assert_eq!(
+            String::from_utf8(out).unwrap(),
+            "{\"name\":\"first\",\"objectID\":\"a\"}\n{\"name\":\"second\",\"objectID\":\"b\"}\n"
+        );
+        assert_eq!(requests.lock().unwrap().len(), 2);
+    }
+
+    #[tokio::test]
+    async fn delete_objects_by_filter() {
+        let (client, requests, bodies) = mock_with_bodies(vec![
+            (
+                200,
+                r#"{"hits":[{"objectID":"a"},{"objectID":"b"}],"cursor":"next","nbHits":3,"processingTimeMS":1}"#,
+            ),
+            (
+                200,
+                r#"{"hits":[{"objectID":"c"}],"nbHits":3,"processingTimeMS":1}"#,
+            ),
+            (200, r#"{"taskID":1,"objectIDs":["a","b","c"]}"#),
+        ]);
+
+        let filter = CommonFilter {
+            invert: false,
+            filter: r#""brand":"acme""#.to_owned(),
+        };
+
+        let response = client
+            .delete_objects_by_filter("products", filter, None)
+            .await
+            .unwrap();
+
+        assert_eq!(response.object_ids, ["a", "b", "c"]);
+        assert_eq!(response.batches.len(), 1);
+        assert_eq!(
+            *requests.lock().unwrap(),
+            [
+                "POST /1/indexes/products/browse",
+                "POST /1/indexes/products/browse",
+                "POST /1/indexes/products/batch",
+            ]
+        );
+
+        let bodies: Vec<serde_json::Value> = bodies
+            .lock()
+            .unwrap()
+            .iter()
+            .map(|it| serde_json::from_str(it).unwrap())
+            .collect();
+
+        assert!(bodies[0].get("cursor").is_none());
+        assert_eq!(bodies[1]["cursor"], "next");
+        assert_eq!(
+            bodies[2],
+            serde_json::json!({
+                "requests": [
+                    {"action": "deleteObject", "body": {"objectID": "a"}},
+                    {"action": "deleteObject", "body": {"objectID": "b"}},
+                    {"action": "deleteObject", "body": {"objectID": "c"}},
+                ]
+            })
+        );
+    }
+
+    #[tokio::test]
+    async fn import_ndjson() {
+        let (client, requests) = mock(vec![(200, r#"{"taskID":1,"objectIDs":["a","b"]}"#)]);
+
+        let ndjson = [
+            r#"{"objectID":"a","name":"first"}"#,
+            r#"{"objectID":"#,
+            r#"{"name":"no id"}"#,
+            "",
+            r#"{"objectID":"b","name":"second"}"#,
+        ]
+        .join("\n");
+
+        let summary = client
+            .import_ndjson("products", ndjson.as_bytes())
+            .await
+            .unwrap();
+
+        assert_eq!(summary.imported, 2);
+        assert_eq!(summary.batches[0].object_ids, ["a", "b"]);
+        assert_eq!(
+            summary.errors.iter().map(|it| it.line).collect::<Vec<_>>(),
+            [2, 3]
+        );
+        assert_eq!(summary.errors[1].message, "missing `objectID`");
+        assert_eq!(
+            *requests.lock().unwrap(),
+            ["POST /1/indexes/products/batch"]
+        );
+    }
+
+    #[test]
+    fn extra_headers() {
+        let mut static_headers = HeaderMap::new();
+        static_headers.insert("X-Gateway-Token", HeaderValue::from_static("gateway"));
+        static_headers.insert("X-Request-Id", HeaderValue::from_static("static"));
+
+        let mut call_headers = HeaderMap::new();
+        call_headers.insert("X-Request-Id", HeaderValue::from_static("call"));
+
+        let client = client().with_headers(static_headers);
+        let per_call = client.clone().with_headers(call_headers);
+
+        let req = client
+            .request(Method::GET, "https://example.com")
+            .build()
+            .unwrap();
+        assert_eq!(req.headers()["X-Gateway-Token"], "gateway");
+        assert_eq!(req.headers()["X-Request-Id"], "static");
+
+        let req = per_call
+            .request(Method::GET, "https://example.com")
+            .build()
+            .unwrap();
+        assert_eq!(req.headers()["X-Gateway-Token"], "gateway");
+        assert_eq!(req.headers()["X-Request-Id"], "call");
+    }
+
+    #[tokio::test]
+    async fn insights_events() {
+        let (client, requests, headers, bodies) = mock_server(vec![
+            (500, "", "{}"),
+            (200, "", r#"{"status":200,"message":"OK"}"#),
+        ]);
+
+        let mut gateway = HeaderMap::new();
+        gateway.insert("X-Gateway-Token", HeaderValue::from_static("gateway"));
+
+        let event = InsightsEvent {
+            event_type: EventType::View,
+            event_name: "Product Viewed".to_owned(),
+            index: "products".to_owned(),
+            user_token: "user-1".to_owned(),
+            query_id: None,
+            object_ids: vec!["a".to_owned()],
+            positions: Vec::new(),
+        };
+
+        let insights = client.with_headers(gateway).insights();
+
+        // sending events isn't idempotent, so the server error isn't retried.
+        let err = insights
+            .send_events(std::slice::from_ref(&event))
+            .await
+            .unwrap_err();
+        assert!(matches!(err, Error::Timeout { attempts: 1, .. }));
+
+        let resp = insights.send_events(&[event]).await.unwrap();
+        assert_eq!(resp.status, 200);
+
+        assert_eq!(
+            *requests.lock().unwrap(),
+            ["POST /1/events", "POST /1/events"]
+        );
+
+        assert!(headers.lock().unwrap()[1]
+            .lines()
+            .any(|it| it == "x-gateway-token: gateway"));
+
+        let body: serde_json::Value = serde_json::from_str(&bodies.lock().unwrap()[1]).unwrap();
+        assert_eq!(body["events"][0]["eventName"], "Product Viewed");
+    }
+
+    #[test]
+    fn search_user_token() {
+        let url = "https://example.com";
+        let header = |req: reqwest::RequestBuilder| {
+            req.build()
+                .unwrap()
+                .headers()
+                .get("X-Algolia-UserToken")
+                .cloned()
+        };
+
+        assert_eq!(header(client().search_request(url, None)), None);
+
+        let client = client().with_user_token("default".to_owned());
+        assert_eq!(header(client.search_request(url, None)).unwrap(), "default");
+        assert_eq!(
+            header(client.search_request(url, Some("query"))).unwrap(),
+            "query"
+        );
+
+        // only searches are affected.
+        assert_eq!(header(client.request(Method::GET, url)), None);
+    }
+
+    #[test]
+    fn batch_chunks() {
+        let requests = (0..5)
+            .map(|it| BatchWriteRequest::UpdateObject {
+                object_id: it.to_string(),
+                body: serde_json::Map::new(),
+            })
+            .collect::<Vec<_>>();
+
+        let chunks = chunk_batch(requests, 2)
+            .map(|it| it.requests.len())
+            .collect::<Vec<_>>();
+
+        assert_eq!(chunks, [2, 2, 1]);
+        assert_eq!(chunk_batch(Vec::new(), 2).count(), 0);
     }
 }
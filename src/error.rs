@@ -1,3 +1,5 @@
+use crate::model::task::TaskId;
+
 /// Internal use type alias
 pub type BoxError = Box<dyn std::error::Error + 'static + Send + Sync>;
 
@@ -8,8 +10,18 @@ pub enum Error {
     #[error("error initializing client: {0}")]
     Configuration(#[source] BoxError),
 
-    #[error("request timed out")]
-    Timeout,
+    /// Every host tried for `route` either timed out or failed with a server error.
+    /// `attempts` is the number of hosts tried, non-idempotent requests are only tried once.
+    #[error("request to `{route}` timed out after {attempts} attempt(s)")]
+    Timeout { route: String, attempts: usize },
+
+    /// A task still wasn't published after `WaitTaskOptions::max_retries` checks.
+    #[error("task {task_id:?} of index `{index}` still not published after {checks} check(s)")]
+    TaskNotPublished {
+        index: String,
+        task_id: TaskId,
+        checks: usize,
+    },
 
     #[error("index `{0}` not found")]
     IndexNotFound(String),
@@ -17,12 +29,63 @@ pub enum Error {
     #[error("decode error: {0}")]
     DecodeError(#[source] BoxError),
 
+    /// Error occurred while serializing records, e.g. with `Client::export_ndjson`
+    #[error("encode error: {0}")]
+    EncodeError(#[source] BoxError),
+
+    /// An Insights event "after search" was built from a search response without a `queryID`
+    #[error("search response has no `queryID`, was `click_analytics` enabled?")]
+    MissingQueryId,
+
     /// Error occurred with a request
     #[error("request error: {0}")]
     RequestError(#[source] BoxError),
+
+    /// Error occurred while reading or writing records, e.g. with `Client::export_ndjson`
+    #[error("io error: {0}")]
+    IoError(#[source] BoxError),
 }
 
 impl Error {
+    /// An `Error::Timeout`, which despite the name also covers server errors:
+    /// every host tried either timed out or answered with a 5xx status.
+    pub fn is_timeout(&self) -> bool {
+        matches!(self, Self::Timeout { .. })
+    }
+
+    /// The index or the object doesn't exist.
+    pub fn is_not_found(&self) -> bool {
+        self.response_status() == Some(404)
+    }
+
+    /// Algolia rejected the request because of too many requests,
+    /// which is worth retrying later, unlike other client errors.
+    pub fn is_rate_limited(&self) -> bool {
+        self.response_status() == Some(429)
+    }
+
+    /// Algolia rejected the request (a 4xx status), e.g. an invalid query, a missing index, or a bad API key.
+    pub fn is_client_error(&self) -> bool {
+        self.response_status()
+            .is_some_and(|status| (400..500).contains(&status))
+    }
+
+    /// The HTTP status of the response this error comes from, if it comes from one.
+    fn response_status(&self) -> Option<u16> {
+        match self {
+            Self::IndexNotFound(_) => Some(404),
+            Self::RequestError(e) => {
+                if let Some(e) = e.downcast_ref::<BadRequestError>() {
+                    Some(e.status.unwrap_or(400))
+                } else {
+                    e.downcast_ref::<UnexpectedResponseError>()
+                        .map(|e| e.status)
+                }
+            }
+            _ => None,
+        }
+    }
+
     pub(crate) async fn bad_request(resp: reqwest::Response) -> Self {
         match resp.json::<BadRequestError>().await {
             Ok(e) => Self::RequestError(Box::new(e)),
@@ -38,10 +101,81 @@ impl Error {
     }
 }
 
+/// A 400 response, usually an invalid query or object.
 #[derive(serde::Deserialize, thiserror::Error, Debug)]
+#[serde(from = "RawBadRequestError")]
 #[error("bad request: {message}")]
 pub struct BadRequestError {
     message: String,
+    status: Option<u16>,
+    kind: BadRequestKind,
+}
+
+impl BadRequestError {
+    pub fn message(&self) -> &str {
+        &self.message
+    }
+
+    /// The HTTP status algolia reports in the body, when it does.
+    pub fn status(&self) -> Option<u16> {
+        self.status
+    }
+
+    /// What went wrong, guessed from the message since algolia doesn't send error codes.
+    pub fn kind(&self) -> &BadRequestKind {
+        &self.kind
+    }
+}
+
+#[derive(serde::Deserialize)]
+struct RawBadRequestError {
+    message: String,
+    status: Option<u16>,
+}
+
+impl From<RawBadRequestError> for BadRequestError {
+    fn from(raw: RawBadRequestError) -> Self {
+        Self {
+            kind: BadRequestKind::classify(&raw.message),
+            message: raw.message,
+            status: raw.status,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum BadRequestKind {
+    /// The `filters` (or `facetFilters`, ...) couldn't be parsed.
+    InvalidFilter,
+    /// The request references an attribute that isn't usable for that purpose,
+    /// e.g. filtering on an attribute missing from `attributesForFaceting`.
+    UnknownAttribute,
+    /// An object ID is empty, too long, or otherwise invalid.
+    InvalidObjectID,
+    /// Anything else, with the message.
+    Other(String),
+}
+
+impl BadRequestKind {
+    fn classify(message: &str) -> Self {
+        let lowercase = message.to_lowercase();
+        let contains = |it: &str| lowercase.contains(it);
+
+        if contains("objectid")
+            && (contains("invalid") || contains("too long") || contains("empty"))
+        {
+            Self::InvalidObjectID
+        } else if contains("unknown attribute") || contains("attributesforfaceting") {
+            Self::UnknownAttribute
+        } else if contains("filter")
+            && (contains("syntax") || contains("unexpected") || contains("invalid"))
+        {
+            Self::InvalidFilter
+        } else {
+            Self::Other(message.to_owned())
+        }
+    }
 }
 
 pub type Result<T, E = Error> = std::result::Result<T, E>;
@@ -53,3 +187,93 @@ pub struct UnexpectedResponseError {
     message: String,
     status: u16,
 }
+
+impl UnexpectedResponseError {
+    pub fn message(&self) -> &str {
+        &self.message
+    }
+
+    pub fn status(&self) -> u16 {
+        self.status
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{BadRequestError, BadRequestKind, Error, UnexpectedResponseError};
+
+    fn kind(message: &str) -> BadRequestKind {
+        let error: BadRequestError =
+            serde_json::from_value(serde_json::json!({ "message": message, "status": 400 }))
+                .unwrap();
+
+        assert_eq!(error.status(), Some(400));
+        assert_eq!(error.message(), message);
+        error.kind().clone()
+    }
+
+    #[test]
+    fn bad_request_kind() {
+        assert_eq!(
+            kind("filters: Unexpected token string(foo) expected end of filter at col 12"),
+            BadRequestKind::InvalidFilter
+        );
+        assert_eq!(
+            kind("Invalid syntax for numeric filter"),
+            BadRequestKind::InvalidFilter
+        );
+        assert_eq!(
+            kind("Cannot filter on `brand`: attribute not in attributesForFaceting"),
+            BadRequestKind::UnknownAttribute
+        );
+        assert_eq!(
+            kind("objectID is too long"),
+            BadRequestKind::InvalidObjectID
+        );
+        assert_eq!(
+            kind("Record at the position 0 objectID=1 is too big size=20480 bytes"),
+            BadRequestKind::Other(
+                "Record at the position 0 objectID=1 is too big size=20480 bytes".to_owned()
+            )
+        );
+    }
+
+    fn unexpected(status: u16) -> Error {
+        Error::RequestError(Box::new(UnexpectedResponseError {
+            message: "oops".to_owned(),
+            status,
+        }))
+    }
+
+    #[test]
+    fn error_classification() {
+        let timeout = Error::Timeout {
+            route: "indexes/products/query".to_owned(),
+            attempts: 4,
+        };
+        assert!(timeout.is_timeout());
+        assert!(!timeout.is_client_error());
+
+        let index_not_found = Error::IndexNotFound("products".to_owned());
+        assert!(index_not_found.is_not_found());
+        assert!(index_not_found.is_client_error());
+        assert!(!index_not_found.is_timeout());
+
+        assert!(unexpected(404).is_not_found());
+
+        let rate_limited = unexpected(429);
+        assert!(rate_limited.is_rate_limited());
+        assert!(rate_limited.is_client_error());
+        assert!(!rate_limited.is_not_found());
+
+        let bad_request: BadRequestError =
+            serde_json::from_value(serde_json::json!({ "message": "invalid filter" })).unwrap();
+        let bad_request = Error::RequestError(Box::new(bad_request));
+        assert!(bad_request.is_client_error());
+        assert!(!bad_request.is_rate_limited());
+
+        let decode = Error::DecodeError("eof".into());
+        assert!(!decode.is_client_error());
+        assert!(!decode.is_not_found());
+    }
+}
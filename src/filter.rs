@@ -2,7 +2,12 @@ use sealed::Sealed;
 use std::fmt::Display;
 
 mod sealed {
-    pub trait Sealed {}
+    pub trait Sealed {
+        /// Whether the filter has a score, which is what `SearchQuery::sum_or_filters_scores` sums.
+        fn has_score(&self) -> bool {
+            false
+        }
+    }
 }
 
 pub trait CommonFilterKind: Display + Sealed {}
@@ -12,8 +17,8 @@ pub trait Filterable: Display + Sealed {}
 // todo: consider making a Filter DSL
 
 macro_rules! make_number_ty {
-    ($number:ident; $( $( #[cfg($attrs:meta)] )? $num:ident($t:ty) ),* $(,)? ) => {
-        #[derive(Copy, Clone, Debug, PartialEq, PartialOrd)]
+    ($( #[$meta:meta] )* $number:ident; $( $( #[cfg($attrs:meta)] )? $num:ident($t:ty) ),* $(,)? ) => {
+        $( #[$meta] )*
         pub enum $number {
             $(
                 $( #[cfg($attrs)] )?
@@ -44,7 +49,34 @@ macro_rules! make_number_ty {
     };
 }
 
-make_number_ty!(Number;
+/// Implements `TryFrom<$from>` by picking the first of the (integer) variants that can hold the value without loss.
+macro_rules! number_try_from {
+    ($number:ident; $from:ty; $( $( #[cfg($attrs:meta)] )? $num:ident($t:ty) ),* $(,)? ) => {
+        impl std::convert::TryFrom<$from> for $number {
+            type Error = NumberOutOfRange;
+
+            fn try_from(value: $from) -> Result<Self, Self::Error> {
+                $(
+                    $( #[cfg($attrs)] )?
+                    if let Ok(num) = <$t as std::convert::TryFrom<$from>>::try_from(value) {
+                        return Ok(Self::$num(num));
+                    }
+                )*
+
+                Err(NumberOutOfRange(value.into()))
+            }
+        }
+    };
+}
+
+/// The integer was too big (or too small) to fit in any of the `Number` variants.
+#[derive(Copy, Clone, Debug, Eq, PartialEq, thiserror::Error)]
+#[error("{0} is out of range for a `Number`")]
+pub struct NumberOutOfRange(pub i128);
+
+make_number_ty!(
+    #[derive(Copy, Clone, Debug, PartialEq, PartialOrd)]
+    Number;
     U8(u8),
     U16(u16),
     U32(u32),
@@ -59,6 +91,123 @@ make_number_ty!(Number;
     F64(f64),
 );
 
+// variants are tried in order, so smaller types go first.
+macro_rules! number_try_from_ints {
+    ($($from:ty),+ $(,)?) => {
+        $(
+            number_try_from!(Number; $from;
+                U8(u8),
+                U16(u16),
+                U32(u32),
+                #[cfg(not(target_pointer_width = "64"))]
+                Usize(usize),
+                I8(i8),
+                I16(i16),
+                I32(i32),
+                I64(i64),
+            );
+        )+
+    };
+}
+
+// Large integers (IDs, timestamps) that would otherwise need a lossy `as` cast.
+number_try_from_ints!(u64, i128);
+
+/// Declares `IntNumber` with these (integer) variants of `Number`, and the conversions between the two.
+macro_rules! make_int_number_ty {
+    ($( $( #[cfg($attrs:meta)] )? $num:ident($t:ty) ),* $(,)? ) => {
+        make_number_ty!(
+            /// A `Number` that's an integer, which unlike `Number` is `Eq`, `Ord` and `Hash`,
+            /// e.g. to use filter values as `HashMap` keys.
+            ///
+            /// Values are compared regardless of the variant, so `IntNumber::U8(1) == IntNumber::I64(1)`.
+            #[derive(Copy, Clone, Debug)]
+            IntNumber;
+            $( $( #[cfg($attrs)] )? $num($t), )*
+        );
+
+        impl std::convert::From<IntNumber> for i128 {
+            fn from(number: IntNumber) -> Self {
+                match number {
+                    $(
+                        $( #[cfg($attrs)] )?
+                        IntNumber::$num(num) => num as i128,
+                    )*
+                }
+            }
+        }
+
+        impl std::convert::From<IntNumber> for Number {
+            fn from(number: IntNumber) -> Self {
+                match number {
+                    $(
+                        $( #[cfg($attrs)] )?
+                        IntNumber::$num(num) => Number::$num(num),
+                    )*
+                }
+            }
+        }
+
+        impl std::convert::TryFrom<Number> for IntNumber {
+            type Error = NotAnInteger;
+
+            fn try_from(number: Number) -> Result<Self, Self::Error> {
+                match number {
+                    $(
+                        $( #[cfg($attrs)] )?
+                        Number::$num(num) => Ok(IntNumber::$num(num)),
+                    )*
+                    _ => Err(NotAnInteger(number)),
+                }
+            }
+        }
+    };
+}
+
+make_int_number_ty!(
+    U8(u8),
+    U16(u16),
+    U32(u32),
+    #[cfg(not(target_pointer_width = "64"))]
+    Usize(usize),
+    I8(i8),
+    I16(i16),
+    I32(i32),
+    I64(i64),
+    Isize(isize),
+);
+
+impl PartialEq for IntNumber {
+    fn eq(&self, other: &Self) -> bool {
+        i128::from(*self) == i128::from(*other)
+    }
+}
+
+impl Eq for IntNumber {}
+
+impl PartialOrd for IntNumber {
+    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for IntNumber {
+    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+        i128::from(*self).cmp(&i128::from(*other))
+    }
+}
+
+impl std::hash::Hash for IntNumber {
+    fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+        i128::from(*self).hash(state)
+    }
+}
+
+/// The `Number` was a float, which `IntNumber` can't hold.
+#[derive(Copy, Clone, Debug, PartialEq, thiserror::Error)]
+#[error("{0} is not an integer")]
+pub struct NotAnInteger(pub Number);
+
 struct AndSeparated<'a, T>(&'a [T], &'static str);
 
 impl<'a, T: Display> Display for AndSeparated<'a, T> {
@@ -130,11 +279,19 @@ impl Display for FilterOperator {
     }
 }
 
+/// Filter on a boolean facet, the value isn't quoted: `"inStock":true`.
+/// Also available as `FacetFilter::boolean`.
 pub struct BooleanFilter {
     pub facet_name: String,
     pub value: bool,
 }
 
+impl BooleanFilter {
+    pub fn new(facet_name: String, value: bool) -> Self {
+        Self { facet_name, value }
+    }
+}
+
 impl Display for BooleanFilter {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         // example of format: "isEnabled":true
@@ -150,11 +307,35 @@ impl Display for TagFilter {
     }
 }
 
+/// Filter on a facet value, the value is quoted: `"category":"5"`.
+///
+/// Facet values are strings, even for numbers, so this matches records with `category` set to `5` or `"5"`.
+/// Booleans aren't quoted though, use `FacetFilter::boolean` (a `BooleanFilter`) for those,
+/// and `CmpFilter` or `RangeFilter` to compare numbers rather than match a facet value.
 pub struct FacetFilter {
     pub facet_name: String,
     pub value: String,
 }
 
+impl FacetFilter {
+    pub fn new(facet_name: String, value: String) -> Self {
+        Self { facet_name, value }
+    }
+
+    /// A facet filter on a number, formatted the same way algolia turns numbers into facet values.
+    pub fn number<T: Into<Number>>(facet_name: String, value: T) -> Self {
+        Self {
+            facet_name,
+            value: value.into().to_string(),
+        }
+    }
+
+    /// A filter on a boolean facet, which is a `BooleanFilter` since booleans aren't quoted.
+    pub fn boolean(facet_name: String, value: bool) -> BooleanFilter {
+        BooleanFilter::new(facet_name, value)
+    }
+}
+
 impl Display for FacetFilter {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         write!(
@@ -180,10 +361,6 @@ pub struct ScoredFacetFilter {
 
 impl Display for ScoredFacetFilter {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        if self.score > i64::MAX {
-            return Err(std::fmt::Error);
-        }
-
         write!(
             f,
             r#"{}:{}<score={}>"#,
@@ -299,6 +476,93 @@ impl Display for EmptyFilter {
         f.write_str("")
     }
 }
+
+/// A single entry of the `facetFilters` array parameter (as opposed to the `filters` string), `facet:value` or `facet:-value`.
+/// See https://www.algolia.com/doc/api-reference/api-parameters/facetFilters/
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct FacetFilterEntry {
+    pub facet: String,
+    pub value: String,
+    /// Exclude the records with this value instead.
+    pub negate: bool,
+}
+
+impl FacetFilterEntry {
+    pub fn new(facet: String, value: String) -> Self {
+        Self {
+            facet,
+            value,
+            negate: false,
+        }
+    }
+
+    pub fn negated(facet: String, value: String) -> Self {
+        Self {
+            facet,
+            value,
+            negate: true,
+        }
+    }
+}
+
+impl Display for FacetFilterEntry {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}:", self.facet)?;
+
+        if self.negate {
+            f.write_str("-")?;
+        } else if self.value.starts_with('-') {
+            // otherwise algolia would take the value's own `-` as a negation.
+            f.write_str("\\")?;
+        }
+
+        f.write_str(&self.value)
+    }
+}
+
+/// Builds the AND-of-ORs `facetFilters` array for `SearchQuery::facet_filters`.
+///
+/// # Examples
+/// ```
+/// use algolia::filter::{FacetFilterEntry, FacetFiltersBuilder};
+///
+/// // brand is apple or samsung, and color isn't red
+/// let facet_filters = FacetFiltersBuilder::default()
+///     .or(vec![
+///         FacetFilterEntry::new("brand".to_owned(), "apple".to_owned()),
+///         FacetFilterEntry::new("brand".to_owned(), "samsung".to_owned()),
+///     ])
+///     .and(FacetFilterEntry::negated("color".to_owned(), "red".to_owned()))
+///     .finish();
+///
+/// assert_eq!(
+///     facet_filters,
+///     vec![vec!["brand:apple", "brand:samsung"], vec!["color:-red"]],
+/// );
+/// ```
+#[derive(Default, Clone, Debug)]
+pub struct FacetFiltersBuilder {
+    filters: Vec<Vec<String>>,
+}
+
+impl FacetFiltersBuilder {
+    /// Records must match `entry`.
+    pub fn and(self, entry: FacetFilterEntry) -> Self {
+        self.or(std::iter::once(entry))
+    }
+
+    /// Records must match at least one of `entries`.
+    pub fn or<I: IntoIterator<Item = FacetFilterEntry>>(mut self, entries: I) -> Self {
+        self.filters
+            .push(entries.into_iter().map(|it| it.to_string()).collect());
+        self
+    }
+
+    pub fn finish(self) -> Vec<Vec<String>> {
+        self.filters
+    }
+}
+
 macro_rules! mark {
     ($mark:ident; $( $t:ty ),+ $(,)? ) => {
         $(
@@ -307,11 +571,32 @@ macro_rules! mark {
     };
 }
 
-mark!(Sealed; BooleanFilter, TagFilter, FacetFilter, ScoredFacetFilter, RangeFilter, CmpFilter, AndFilter, EmptyFilter, String);
+mark!(Sealed; BooleanFilter, TagFilter, FacetFilter, RangeFilter, CmpFilter, EmptyFilter, String);
 mark!(CommonFilterKind; BooleanFilter, TagFilter, FacetFilter, ScoredFacetFilter, RangeFilter, CmpFilter, String);
 
-impl<T: CommonFilterKind> Sealed for OrFilter<T> {}
-impl<T: CommonFilterKind> Sealed for CommonFilter<T> {}
+impl Sealed for ScoredFacetFilter {
+    fn has_score(&self) -> bool {
+        true
+    }
+}
+
+impl Sealed for AndFilter {
+    fn has_score(&self) -> bool {
+        self.filters.iter().any(|it| it.has_score())
+    }
+}
+
+impl<T: CommonFilterKind> Sealed for OrFilter<T> {
+    fn has_score(&self) -> bool {
+        self.filters.iter().any(|it| it.has_score())
+    }
+}
+
+impl<T: CommonFilterKind> Sealed for CommonFilter<T> {
+    fn has_score(&self) -> bool {
+        self.filter.has_score()
+    }
+}
 
 impl<T: CommonFilterKind> AndFilterable for OrFilter<T> {}
 impl<T: CommonFilterKind> AndFilterable for CommonFilter<T> {}
@@ -322,3 +607,96 @@ impl<T: CommonFilterKind> Filterable for CommonFilter<T> {}
 impl Filterable for EmptyFilter {}
 
 // todo: add a heckton of tests.
+
+#[cfg(test)]
+mod test {
+    use super::{
+        FacetFilter, FacetFilterEntry, IntNumber, NotAnInteger, Number, NumberOutOfRange,
+        RangeFilter,
+    };
+    use std::{collections::HashSet, convert::TryFrom};
+
+    #[test]
+    fn typed_facet_filters() {
+        assert_eq!(
+            FacetFilter::boolean("inStock".to_owned(), true).to_string(),
+            r#""inStock":true"#
+        );
+        assert_eq!(
+            FacetFilter::number("category".to_owned(), 5_u8).to_string(),
+            r#""category":"5""#
+        );
+        assert_eq!(
+            FacetFilter::new("category".to_owned(), "5".to_owned()).to_string(),
+            FacetFilter::number("category".to_owned(), 5_u8).to_string()
+        );
+    }
+
+    #[test]
+    fn int_number() {
+        assert_eq!(IntNumber::try_from(Number::U32(42)), Ok(IntNumber::U32(42)));
+        assert_eq!(
+            IntNumber::try_from(Number::F64(4.2)),
+            Err(NotAnInteger(Number::F64(4.2)))
+        );
+        assert_eq!(Number::from(IntNumber::I64(-1)), Number::I64(-1));
+
+        // same value, different variants.
+        assert_eq!(IntNumber::U8(1), IntNumber::I64(1));
+        assert!(IntNumber::I8(-1) < IntNumber::U8(0));
+
+        let ids: HashSet<_> = vec![IntNumber::U8(1), IntNumber::I32(1), IntNumber::U16(300)]
+            .into_iter()
+            .collect();
+        assert_eq!(ids.len(), 2);
+    }
+
+    #[test]
+    fn number_try_from_picks_narrowest() {
+        assert_eq!(Number::try_from(200_u64), Ok(Number::U8(200)));
+        assert_eq!(Number::try_from(70_000_u64), Ok(Number::U32(70_000)));
+        assert_eq!(Number::try_from(i64::MAX as u64), Ok(Number::I64(i64::MAX)));
+        assert_eq!(Number::try_from(-1_i128), Ok(Number::I8(-1)));
+        assert_eq!(Number::try_from(-40_000_i128), Ok(Number::I32(-40_000)));
+    }
+
+    #[test]
+    fn number_try_from_out_of_range() {
+        assert_eq!(
+            Number::try_from(u64::MAX),
+            Err(NumberOutOfRange(u64::MAX.into()))
+        );
+        assert_eq!(
+            Number::try_from(i128::from(i64::MIN) - 1),
+            Err(NumberOutOfRange(i128::from(i64::MIN) - 1))
+        );
+    }
+
+    #[test]
+    fn range_filter_large_integers() {
+        let filter = RangeFilter {
+            attribute_name: "timestamp".to_owned(),
+            lower_bound: Number::try_from(1_628_000_000_000_u64).unwrap(),
+            upper_bound: Number::try_from(9_007_199_254_740_993_u64).unwrap(),
+        };
+
+        assert_eq!(
+            filter.to_string(),
+            r#""timestamp": 1628000000000 TO 9007199254740993"#
+        );
+    }
+
+    #[test]
+    fn facet_filter_entries() {
+        let entry = |value: &str, negate| FacetFilterEntry {
+            facet: "brand".to_owned(),
+            value: value.to_owned(),
+            negate,
+        };
+
+        assert_eq!(entry("apple", false).to_string(), "brand:apple");
+        assert_eq!(entry("apple", true).to_string(), "brand:-apple");
+        assert_eq!(entry("-apple", false).to_string(), r"brand:\-apple");
+        assert_eq!(entry("-apple", true).to_string(), "brand:--apple");
+    }
+}
@@ -4,42 +4,69 @@ use std::{
     num::NonZeroUsize,
 };
 
+/// A host of the API, formatted as `{name}[-dsn][-{backup_number}].{domain}`.
 pub struct Host<'a> {
-    app_id: &'a RefAppId,
+    name: &'a str,
     dsn: bool,
     backup_number: Option<NonZeroUsize>,
+    domain: &'a str,
 }
 
 impl<'a> Host<'a> {
+    /// The domain of the search hosts of an application.
+    pub const APPLICATION_DOMAIN: &'static str = "algolia.net";
+
+    /// The main search host of an application, `{app_id}.algolia.net`.
     pub fn new(app_id: &'a RefAppId) -> Self {
         Self {
-            app_id,
+            name: app_id.as_str(),
             dsn: false,
             backup_number: None,
+            domain: Self::APPLICATION_DOMAIN,
         }
     }
 
+    /// The search host of an application, or its DSN host (`{app_id}-dsn.algolia.net`) when `dsn` is set,
+    /// which serves reads from the closest replica.
     pub fn with_dsn(app_id: &'a RefAppId, dsn: bool) -> Self {
         Self {
-            app_id,
             dsn,
-            backup_number: None,
+            ..Self::new(app_id)
         }
     }
 
     /// Note: backup_number of `0` is the same thing as `None`
     pub fn with_backup(app_id: &'a RefAppId, backup_number: Option<usize>) -> Self {
         Self {
-            app_id,
-            dsn: false,
             backup_number: backup_number.and_then(NonZeroUsize::new),
+            ..Self::new(app_id)
+        }
+    }
+
+    /// The host of an API that isn't specific to an application, like `insights.algolia.io`.
+    pub fn service(name: &'a str, domain: &'a str) -> Self {
+        Self {
+            name,
+            dsn: false,
+            backup_number: None,
+            domain,
         }
     }
+
+    /// The Insights API host.
+    pub fn insights() -> Self {
+        Self::service("insights", "algolia.io")
+    }
+
+    /// The Analytics API host.
+    pub fn analytics() -> Self {
+        Self::service("analytics", "algolia.com")
+    }
 }
 
 impl<'a> Display for Host<'a> {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        f.write_str(self.app_id.as_ref())?;
+        f.write_str(self.name)?;
         if self.dsn {
             f.write_str("-dsn")?;
         }
@@ -48,6 +75,33 @@ impl<'a> Display for Host<'a> {
             write!(f, "-{}", backup_number)?;
         }
 
-        f.write_str(".algolia.net")
+        write!(f, ".{}", self.domain)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::Host;
+    use crate::AppId;
+
+    #[test]
+    fn hosts() {
+        let app_id = AppId::new("APPID".to_owned());
+
+        assert_eq!(Host::new(&app_id).to_string(), "APPID.algolia.net");
+        assert_eq!(
+            Host::with_dsn(&app_id, true).to_string(),
+            "APPID-dsn.algolia.net"
+        );
+        assert_eq!(
+            Host::with_backup(&app_id, Some(2)).to_string(),
+            "APPID-2.algolia.net"
+        );
+        assert_eq!(
+            Host::with_backup(&app_id, Some(0)).to_string(),
+            "APPID.algolia.net"
+        );
+        assert_eq!(Host::insights().to_string(), "insights.algolia.io");
+        assert_eq!(Host::analytics().to_string(), "analytics.algolia.com");
     }
 }
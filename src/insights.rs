@@ -0,0 +1,225 @@
+//! Client for the Insights API, used to send click, conversion and view events.
+//! See https://www.algolia.com/doc/rest-api/insights/
+
+use crate::{
+    client::{check_response, decode, Route},
+    host::Host,
+    response::SearchResponse,
+    ApiKey, AppId, Client, Error, Result,
+};
+use reqwest::{header::HeaderMap, Method};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+#[derive(Serialize, Debug, Copy, Clone, Eq, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub enum EventType {
+    Click,
+    Conversion,
+    View,
+}
+
+/// A single Insights event.
+/// See https://www.algolia.com/doc/rest-api/insights/#push-events
+#[derive(Serialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct InsightsEvent {
+    pub event_type: EventType,
+
+    pub event_name: String,
+
+    pub index: String,
+
+    pub user_token: String,
+
+    /// The `queryID` of the search that led to this event, required for events "after search".
+    #[serde(rename = "queryID")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub query_id: Option<String>,
+
+    #[serde(rename = "objectIDs")]
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub object_ids: Vec<String>,
+
+    /// Positions of the `object_ids` in the search results, starting at 1.
+    /// Required (and only allowed) for click events after a search.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub positions: Vec<usize>,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct InsightsResponse {
+    pub status: u16,
+    pub message: String,
+}
+
+/// The `events` route of the Insights API, served by `Host::insights()`.
+struct EventsRoute;
+
+impl fmt::Display for EventsRoute {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("events")
+    }
+}
+
+impl Route for EventsRoute {
+    // the events of a batch would be recorded twice.
+    fn idempotent(&self) -> bool {
+        false
+    }
+
+    fn service_host(&self) -> Option<Host<'static>> {
+        Some(Host::insights())
+    }
+}
+
+/// Requests are sent through the same retry logic as `Client`'s, to `Host::insights()`.
+#[derive(Clone, Debug)]
+pub struct InsightsClient {
+    client: Client,
+}
+
+impl InsightsClient {
+    pub fn new(application_id: AppId, api_key: ApiKey) -> Result<Self> {
+        Client::new(application_id, api_key).map(Self::with_client)
+    }
+
+    pub(crate) fn with_client(client: Client) -> Self {
+        Self { client }
+    }
+
+    /// Send `headers` with every request made by this client, replacing any header with the same name,
+    /// like `Client::with_headers`.
+    pub fn with_headers(mut self, headers: HeaderMap) -> Self {
+        self.client = self.client.with_headers(headers);
+        self
+    }
+
+    /// Send a batch of events.
+    pub async fn send_events(&self, events: &[InsightsEvent]) -> Result<InsightsResponse> {
+        #[derive(Serialize)]
+        struct Request<'a> {
+            events: &'a [InsightsEvent],
+        }
+
+        self.client
+            .retry_with(EventsRoute, |url| async move {
+                let resp = self
+                    .client
+                    .request(Method::POST, &url)
+                    .json(&Request { events })
+                    .send()
+                    .await;
+
+                match check_response(resp, None).await? {
+                    Some(resp) => decode(resp).await,
+                    None => Ok(None),
+                }
+            })
+            .await
+    }
+
+    /// Send a click event for `object_id` clicked at `position` in the results of `search_response`.
+    ///
+    /// The `queryID` is taken from `search_response`, which requires the search to have been made with `click_analytics` enabled,
+    /// otherwise this returns `Error::MissingQueryId` without sending anything.
+    pub async fn clicked_after_search<T, F>(
+        &self,
+        index: &str,
+        user_token: &str,
+        event_name: &str,
+        search_response: &SearchResponse<T, F>,
+        object_id: &str,
+        position: usize,
+    ) -> Result<InsightsResponse> {
+        let event = clicked_after_search_event(
+            index,
+            user_token,
+            event_name,
+            search_response,
+            object_id,
+            position,
+        )?;
+
+        self.send_events(std::slice::from_ref(&event)).await
+    }
+}
+
+fn clicked_after_search_event<T, F>(
+    index: &str,
+    user_token: &str,
+    event_name: &str,
+    search_response: &SearchResponse<T, F>,
+    object_id: &str,
+    position: usize,
+) -> Result<InsightsEvent> {
+    let query_id = search_response
+        .query_id
+        .clone()
+        .ok_or(Error::MissingQueryId)?;
+
+    Ok(InsightsEvent {
+        event_type: EventType::Click,
+        event_name: event_name.to_owned(),
+        index: index.to_owned(),
+        user_token: user_token.to_owned(),
+        query_id: Some(query_id),
+        object_ids: vec![object_id.to_owned()],
+        positions: vec![position],
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use super::clicked_after_search_event;
+    use crate::{response::SearchResponse, Error};
+
+    fn search_response(query_id: Option<&str>) -> SearchResponse {
+        let mut resp = serde_json::json!({
+            "hits": [{ "objectID": "a" }],
+            "page": 0,
+            "nbHits": 1,
+            "nbPages": 1,
+            "hitsPerPage": 20,
+            "processingTimeMS": 1,
+            "query": "shoes",
+            "params": "query=shoes&clickAnalytics=true",
+        });
+
+        if let Some(query_id) = query_id {
+            resp["queryID"] = query_id.into();
+        }
+
+        serde_json::from_value(resp).unwrap()
+    }
+
+    #[test]
+    fn clicked_after_search() {
+        let event = clicked_after_search_event(
+            "products",
+            "user-1",
+            "Product Clicked",
+            &search_response(Some("43b15df305339e827f0ac0bdc5ebcaa7")),
+            "a",
+            1,
+        )
+        .unwrap();
+
+        insta::assert_json_snapshot!(event);
+    }
+
+    #[test]
+    fn clicked_after_search_without_query_id() {
+        let err = clicked_after_search_event(
+            "products",
+            "user-1",
+            "Product Clicked",
+            &search_response(None),
+            "a",
+            1,
+        )
+        .unwrap_err();
+
+        assert!(matches!(err, Error::MissingQueryId));
+    }
+}
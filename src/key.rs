@@ -26,7 +26,7 @@ impl ApiKey {
     pub fn generate_virtual_key(&self, restrictions: &VirtualKeyRestrictions) -> ApiKey {
         use hmac::{Hmac, Mac, NewMac};
 
-        let mut restrictions = serde_urlencoded::to_string(&restrictions)
+        let mut restrictions = serde_urlencoded::to_string(restrictions)
             .expect("We control `restrictions`' format, it shouldn't error");
 
         // HACK: algolia doesn't understand empty `restrictions`
@@ -39,7 +39,7 @@ impl ApiKey {
         let mut mac = Hmac::<sha2::Sha256>::new_from_slice(self.0.as_bytes())
             .expect("HMAC can take key of any size");
 
-        mac.update(&restrictions.as_bytes());
+        mac.update(restrictions.as_bytes());
 
         // note: we aren't doing any equality checks, so the warning doesn't apply.
         let key = mac.finalize().into_bytes();
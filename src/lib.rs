@@ -1,12 +1,18 @@
+#[cfg(not(any(feature = "native-tls", feature = "rustls-tls")))]
+compile_error!("either the `native-tls` or the `rustls-tls` feature must be enabled");
+
 mod app_id;
 mod client;
 pub mod error;
 pub mod filter;
-mod host;
+pub mod host;
+pub mod insights;
 mod key;
 pub mod model;
+pub mod recommend;
 pub mod request;
 pub mod response;
+pub mod stats;
 
 pub use app_id::{AppId, RefAppId};
 pub use client::Client;
@@ -1,2 +1,7 @@
 pub mod attribute;
+pub mod geo;
+pub mod language;
+pub mod rule;
+pub mod synonym;
 pub mod task;
+pub mod typo;
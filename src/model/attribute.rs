@@ -1,10 +1,28 @@
-use std::fmt::{self, Display};
+use std::{
+    convert::Infallible,
+    fmt::{self, Display},
+    str::FromStr,
+};
 
 // an attribute is `[A-Za-z0-9\. ]+` presumably?
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub struct Attribute(pub String);
 
-#[derive(Debug, Clone)]
+impl Display for Attribute {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl FromStr for Attribute {
+    type Err = Infallible;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(Self(s.to_owned()))
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
 struct SearchableAttribue {
     unordered: bool,
     // note: all of these share the same priority
@@ -36,7 +54,7 @@ impl Display for SearchableAttribue {
     }
 }
 
-#[derive(serde::Serialize, Debug, Clone)]
+#[derive(serde::Serialize, serde::Deserialize, Debug, Clone, PartialEq, Eq)]
 pub struct SearchableAttributes(Vec<SearchableAttribue>);
 
 impl SearchableAttributes {
@@ -104,10 +122,53 @@ impl serde::Serialize for SearchableAttribue {
     }
 }
 
+#[derive(Debug, thiserror::Error, Clone, PartialEq, Eq)]
+#[error("invalid searchable attribute: `{0}`")]
+pub struct InvalidSearchableAttribute(pub String);
+
+/// Parses what `Display` outputs, which is also what algolia returns in `searchableAttributes`:
+/// `title`, `title,alternative_title` (algolia may add spaces after the commas), `unordered(title)`, ...
+impl FromStr for SearchableAttribue {
+    type Err = InvalidSearchableAttribute;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || InvalidSearchableAttribute(s.to_owned());
+
+        let (unordered, attributes) = match s.strip_prefix("unordered(") {
+            Some(inner) => (true, inner.strip_suffix(')').ok_or_else(invalid)?),
+            None => (false, s),
+        };
+
+        let attributes = attributes
+            .split(',')
+            .map(|attribute| match attribute.trim() {
+                "" => Err(invalid()),
+                attribute if attribute.contains(&['(', ')'][..]) => Err(invalid()),
+                attribute => Ok(Attribute(attribute.to_owned())),
+            })
+            .collect::<Result<_, _>>()?;
+
+        Ok(Self {
+            unordered,
+            attributes,
+        })
+    }
+}
+
+impl<'de> serde::Deserialize<'de> for SearchableAttribue {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        let s = String::deserialize(deserializer)?;
+        s.parse().map_err(serde::de::Error::custom)
+    }
+}
+
 /// By default, setting a Facet enables both faceting and filtering, this can modify that to either limit it to filtering, or to also add searching.
 /// See https://www.algolia.com/doc/api-reference/api-parameters/attributesForFaceting/
 /// See https://www.algolia.com/doc/api-reference/api-methods/search-for-facet-values/
-#[derive(Copy, Clone, Debug)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
 pub enum FacetModifier {
     FilterOnly,
     Searchable,
@@ -122,10 +183,13 @@ impl FacetModifier {
     }
 }
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, PartialEq, Eq)]
 pub struct FacetAttribute {
     pub attribute: Attribute,
     pub modifier: Option<FacetModifier>,
+    /// Compute the facet counts after deduplication, when `distinct` is enabled.
+    /// See https://www.algolia.com/doc/api-reference/api-parameters/attributesForFaceting/#modifiers
+    pub after_distinct: bool,
 }
 
 impl FacetAttribute {
@@ -133,6 +197,7 @@ impl FacetAttribute {
         Self {
             attribute,
             modifier: None,
+            after_distinct: false,
         }
     }
 
@@ -140,6 +205,7 @@ impl FacetAttribute {
         Self {
             attribute,
             modifier,
+            after_distinct: false,
         }
     }
 
@@ -147,6 +213,7 @@ impl FacetAttribute {
         Self {
             attribute,
             modifier: Some(FacetModifier::FilterOnly),
+            after_distinct: false,
         }
     }
 
@@ -154,7 +221,34 @@ impl FacetAttribute {
         Self {
             attribute,
             modifier: Some(FacetModifier::Searchable),
+            after_distinct: false,
+        }
+    }
+
+    /// Wrap this attribute in `afterDistinct(...)`, works with any modifier.
+    pub fn after_distinct(mut self) -> Self {
+        self.after_distinct = true;
+        self
+    }
+}
+
+impl Display for FacetAttribute {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.after_distinct {
+            write!(f, "afterDistinct(")?;
+        }
+
+        if let Some(modifier) = self.modifier {
+            write!(f, "{}({})", modifier.as_str(), &self.attribute.0)?;
+        } else {
+            f.write_str(&self.attribute.0)?;
+        }
+
+        if self.after_distinct {
+            write!(f, ")")?;
         }
+
+        Ok(())
     }
 }
 
@@ -163,21 +257,122 @@ impl serde::Serialize for FacetAttribute {
     where
         S: serde::Serializer,
     {
+        serializer.collect_str(self)
+    }
+}
+
+/// Restricts how a numeric attribute can be filtered on.
+/// See https://www.algolia.com/doc/api-reference/api-parameters/numericAttributesForFiltering/
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum NumericModifier {
+    /// Only `=` and `!=` comparisons, no ranges, which saves some space in the index.
+    EqualOnly,
+}
+
+impl NumericModifier {
+    pub const fn as_str(self) -> &'static str {
+        match self {
+            Self::EqualOnly => "equalOnly",
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct NumericAttribute {
+    pub attribute: Attribute,
+    pub modifier: Option<NumericModifier>,
+}
+
+impl NumericAttribute {
+    pub fn new(attribute: Attribute) -> Self {
+        Self {
+            attribute,
+            modifier: None,
+        }
+    }
+
+    pub fn equal_only(attribute: Attribute) -> Self {
+        Self {
+            attribute,
+            modifier: Some(NumericModifier::EqualOnly),
+        }
+    }
+}
+
+impl Display for NumericAttribute {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         if let Some(modifier) = self.modifier {
-            serializer.collect_str(&format_args!(
-                "{}({})",
-                modifier.as_str(),
-                &self.attribute.0
-            ))
+            write!(f, "{}({})", modifier.as_str(), &self.attribute.0)
         } else {
-            serializer.serialize_str(&self.attribute.0)
+            f.write_str(&self.attribute.0)
         }
     }
 }
 
+impl serde::Serialize for NumericAttribute {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        serializer.collect_str(self)
+    }
+}
+
+#[derive(Debug, thiserror::Error, Clone, PartialEq, Eq)]
+#[error("invalid facet attribute: `{0}`")]
+pub struct InvalidFacetAttribute(pub String);
+
+/// Parses what `Display` outputs, which is also what algolia returns in `attributesForFaceting`:
+/// `brand`, `filterOnly(brand)`, `searchable(brand)`, `afterDistinct(brand)`, `afterDistinct(searchable(brand))`, ...
+impl FromStr for FacetAttribute {
+    type Err = InvalidFacetAttribute;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || InvalidFacetAttribute(s.to_owned());
+
+        let mut rest = s;
+        let mut modifier = None;
+        let mut after_distinct = false;
+
+        while let Some((function, inner)) = rest.split_once('(') {
+            match function {
+                "afterDistinct" if !after_distinct => after_distinct = true,
+                "filterOnly" if modifier.is_none() => modifier = Some(FacetModifier::FilterOnly),
+                "searchable" if modifier.is_none() => modifier = Some(FacetModifier::Searchable),
+                _ => return Err(invalid()),
+            }
+
+            rest = inner.strip_suffix(')').ok_or_else(invalid)?;
+        }
+
+        if rest.is_empty() || rest.contains(')') {
+            return Err(invalid());
+        }
+
+        Ok(Self {
+            attribute: Attribute(rest.to_owned()),
+            modifier,
+            after_distinct,
+        })
+    }
+}
+
+impl<'de> serde::Deserialize<'de> for FacetAttribute {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        let s = String::deserialize(deserializer)?;
+        s.parse().map_err(serde::de::Error::custom)
+    }
+}
+
 #[cfg(test)]
 mod test {
-    use super::{Attribute, FacetAttribute, SearchableAttributes};
+    use super::{
+        Attribute, FacetAttribute, InvalidFacetAttribute, InvalidSearchableAttribute,
+        NumericAttribute, SearchableAttribue, SearchableAttributes,
+    };
 
     #[test]
     fn list_of_attributes() {
@@ -189,6 +384,40 @@ mod test {
             .finish())
     }
 
+    #[test]
+    fn searchable_attributes_round_trip() {
+        let attributes = SearchableAttributes::build()
+            .single(Attribute("a".to_owned()))
+            .multi(vec![Attribute("b".to_owned()), Attribute("c".to_owned())])
+            .single_unordered(Attribute("e".to_owned()))
+            .multi_unordered(vec![Attribute("f".to_owned()), Attribute("g".to_owned())])
+            .finish();
+
+        let json = serde_json::to_string(&attributes).unwrap();
+        let parsed: SearchableAttributes = serde_json::from_str(&json).unwrap();
+        assert_eq!(parsed, attributes);
+
+        let parsed: SearchableAttributes =
+            serde_json::from_str(r#"["b, c", "unordered(f, g)"]"#).unwrap();
+        assert_eq!(
+            parsed,
+            SearchableAttributes::build()
+                .multi(vec![Attribute("b".to_owned()), Attribute("c".to_owned())])
+                .multi_unordered(vec![Attribute("f".to_owned()), Attribute("g".to_owned())])
+                .finish()
+        );
+    }
+
+    #[test]
+    fn invalid_searchable_attributes() {
+        for invalid in &["", "a,", "unordered()", "unordered(a", "ordered(a)", "a)"] {
+            assert_eq!(
+                invalid.parse::<SearchableAttribue>(),
+                Err(InvalidSearchableAttribute((*invalid).to_owned()))
+            );
+        }
+    }
+
     #[test]
     fn facet_attributes() {
         insta::assert_json_snapshot!(vec![
@@ -197,4 +426,60 @@ mod test {
             FacetAttribute::searchable(Attribute("b".to_owned())),
         ])
     }
+
+    #[test]
+    fn after_distinct_facet_attributes() {
+        insta::assert_json_snapshot!(vec![
+            FacetAttribute::new(Attribute("category".to_owned())).after_distinct(),
+            FacetAttribute::filter_only(Attribute("brand".to_owned())).after_distinct(),
+            FacetAttribute::searchable(Attribute("color".to_owned())).after_distinct(),
+        ])
+    }
+
+    #[test]
+    fn numeric_attributes() {
+        insta::assert_json_snapshot!(vec![
+            NumericAttribute::new(Attribute("price".to_owned())),
+            NumericAttribute::equal_only(Attribute("year".to_owned())),
+        ])
+    }
+
+    #[test]
+    fn facet_attribute_round_trip() {
+        let attributes = vec![
+            FacetAttribute::new(Attribute("category".to_owned())),
+            FacetAttribute::filter_only(Attribute("brand".to_owned())),
+            FacetAttribute::searchable(Attribute("author.name".to_owned())),
+            FacetAttribute::new(Attribute("color".to_owned())).after_distinct(),
+            FacetAttribute::searchable(Attribute("size".to_owned())).after_distinct(),
+        ];
+
+        let json = serde_json::to_string(&attributes).unwrap();
+        let parsed: Vec<FacetAttribute> = serde_json::from_str(&json).unwrap();
+        assert_eq!(parsed, attributes);
+
+        // algolia may nest the other way around.
+        assert_eq!(
+            "searchable(afterDistinct(size))".parse(),
+            Ok(FacetAttribute::searchable(Attribute("size".to_owned())).after_distinct())
+        );
+    }
+
+    #[test]
+    fn invalid_facet_attributes() {
+        for invalid in &[
+            "",
+            "filterOnly()",
+            "filterOnly(brand",
+            "unknown(brand)",
+            "filterOnly(searchable(brand))",
+            "afterDistinct(afterDistinct(brand))",
+            "brand)",
+        ] {
+            assert_eq!(
+                invalid.parse::<FacetAttribute>(),
+                Err(InvalidFacetAttribute((*invalid).to_owned()))
+            );
+        }
+    }
 }
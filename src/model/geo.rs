@@ -0,0 +1,221 @@
+use serde::{Deserialize, Serialize};
+
+/// Mean earth radius, in meters.
+const EARTH_RADIUS_M: f64 = 6_371_008.8;
+
+/// A rectangular area, as `[p1_lat, p1_lng, p2_lat, p2_lng]` where `p1` and `p2` are opposite corners.
+/// See https://www.algolia.com/doc/api-reference/api-parameters/insideBoundingBox/
+#[derive(Serialize, Deserialize, Debug, Copy, Clone, PartialEq)]
+pub struct BoundingBox(pub [f64; 4]);
+
+impl BoundingBox {
+    /// The smallest box containing the circle of `radius_m` meters around (`lat`, `lng`).
+    ///
+    /// A degree of longitude gets shorter as the latitude increases, so the box gets wider (in degrees) away from the equator.
+    /// Near the poles, the box spans every longitude.
+    pub fn around(lat: f64, lng: f64, radius_m: f64) -> Self {
+        let distance = radius_m / EARTH_RADIUS_M;
+        let lat_delta = distance.to_degrees();
+
+        // the widest point of the circle isn't at `lat`, hence the `asin`.
+        let sin_lng_delta = distance.sin() / lat.to_radians().cos();
+
+        let lng_delta = if lat.abs() + lat_delta >= 90.0 || sin_lng_delta >= 1.0 {
+            180.0
+        } else {
+            sin_lng_delta.asin().to_degrees()
+        };
+
+        let (lng_min, lng_max) = if lng_delta >= 180.0 {
+            (-180.0, 180.0)
+        } else {
+            (wrap_lng(lng - lng_delta), wrap_lng(lng + lng_delta))
+        };
+
+        Self([
+            (lat - lat_delta).max(-90.0),
+            lng_min,
+            (lat + lat_delta).min(90.0),
+            lng_max,
+        ])
+    }
+}
+
+impl From<BoundingBox> for [f64; 4] {
+    fn from(bounding_box: BoundingBox) -> Self {
+        bounding_box.0
+    }
+}
+
+/// A polygon, as the flat `[p1_lat, p1_lng, p2_lat, p2_lng, ...]` array of its points.
+/// See https://www.algolia.com/doc/api-reference/api-parameters/insidePolygon/
+///
+/// Use `Polygon::new` to get one, which makes sure it has enough points.
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct Polygon(Vec<f64>);
+
+impl Polygon {
+    /// A polygon with these (`lat`, `lng`) points, in order.
+    ///
+    /// Polygons are closed implicitly, if the last point repeats the first one it's dropped.
+    pub fn new(points: impl IntoIterator<Item = (f64, f64)>) -> Result<Self, InvalidPolygon> {
+        let mut points: Vec<_> = points.into_iter().collect();
+
+        if points.len() > 1 && points.first() == points.last() {
+            points.pop();
+        }
+
+        if points.len() < 3 {
+            return Err(InvalidPolygon::TooFewPoints(points.len()));
+        }
+
+        Ok(Self(
+            points
+                .into_iter()
+                .flat_map(|(lat, lng)| [lat, lng])
+                .collect(),
+        ))
+    }
+
+    /// The (`lat`, `lng`) points of the polygon.
+    pub fn points(&self) -> impl Iterator<Item = (f64, f64)> + '_ {
+        self.0.chunks_exact(2).map(|point| (point[0], point[1]))
+    }
+}
+
+impl From<Polygon> for Vec<f64> {
+    fn from(polygon: Polygon) -> Self {
+        polygon.0
+    }
+}
+
+#[derive(Debug, thiserror::Error, Copy, Clone, Eq, PartialEq)]
+pub enum InvalidPolygon {
+    #[error("a polygon needs at least 3 distinct points, got {0}")]
+    TooFewPoints(usize),
+}
+
+/// The maximum distance of hits around `around_lat_lng`.
+/// See https://www.algolia.com/doc/api-reference/api-parameters/aroundRadius/
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum AroundRadius {
+    /// No maximum distance, hits are only ranked by distance. Serialized as `"all"`.
+    All,
+    /// Serialized as a number, not a string.
+    Meters(u32),
+}
+
+impl Serialize for AroundRadius {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        match self {
+            Self::All => serializer.serialize_str("all"),
+            Self::Meters(meters) => serializer.serialize_u32(*meters),
+        }
+    }
+}
+
+// keeps longitudes in -180..=180, a box crossing the antimeridian ends up with `p1_lng > p2_lng`.
+fn wrap_lng(lng: f64) -> f64 {
+    if lng > 180.0 {
+        lng - 360.0
+    } else if lng < -180.0 {
+        lng + 360.0
+    } else {
+        lng
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{AroundRadius, BoundingBox, InvalidPolygon, Polygon};
+
+    fn assert_close(actual: [f64; 4], expected: [f64; 4]) {
+        for (actual, expected) in actual.iter().zip(&expected) {
+            assert!(
+                (actual - expected).abs() < 1e-3,
+                "{:?} != {:?}",
+                actual,
+                expected
+            );
+        }
+    }
+
+    // ~111.2km is a degree of latitude.
+    const DEGREE_M: f64 = 111_195.0;
+
+    #[test]
+    fn around_equator() {
+        assert_close(
+            BoundingBox::around(0.0, 0.0, DEGREE_M).0,
+            [-1.0, -1.0, 1.0, 1.0],
+        );
+    }
+
+    #[test]
+    fn around_high_latitude() {
+        // cos(60°) = 0.5, so a degree of longitude is half as long.
+        assert_close(
+            BoundingBox::around(60.0, 10.0, DEGREE_M).0,
+            [59.0, 8.0, 61.0, 12.0],
+        );
+    }
+
+    #[test]
+    fn around_pole() {
+        assert_close(
+            BoundingBox::around(89.5, 0.0, DEGREE_M).0,
+            [88.5, -180.0, 90.0, 180.0],
+        );
+    }
+
+    #[test]
+    fn around_antimeridian() {
+        assert_close(
+            BoundingBox::around(0.0, 179.5, DEGREE_M).0,
+            [-1.0, 178.5, 1.0, -179.5],
+        );
+    }
+
+    #[test]
+    fn around_radius() {
+        assert_eq!(
+            serde_json::to_string(&[AroundRadius::All, AroundRadius::Meters(5000)]).unwrap(),
+            r#"["all",5000]"#
+        );
+    }
+
+    #[test]
+    fn polygon() {
+        let polygon = Polygon::new(vec![(46.0, 6.0), (47.0, 6.5), (46.5, 7.5)]).unwrap();
+
+        assert_eq!(
+            serde_json::to_string(&polygon).unwrap(),
+            "[46.0,6.0,47.0,6.5,46.5,7.5]"
+        );
+        assert_eq!(polygon.points().count(), 3);
+
+        // the closing point is implicit.
+        assert_eq!(
+            Polygon::new(vec![(46.0, 6.0), (47.0, 6.5), (46.5, 7.5), (46.0, 6.0)]),
+            Ok(polygon)
+        );
+    }
+
+    #[test]
+    fn polygon_too_few_points() {
+        assert_eq!(
+            Polygon::new(vec![(46.0, 6.0), (47.0, 6.5)]),
+            Err(InvalidPolygon::TooFewPoints(2))
+        );
+
+        assert_eq!(
+            Polygon::new(vec![(46.0, 6.0), (47.0, 6.5), (46.0, 6.0)]),
+            Err(InvalidPolygon::TooFewPoints(2))
+        );
+
+        assert_eq!(Polygon::new(vec![]), Err(InvalidPolygon::TooFewPoints(0)));
+    }
+}
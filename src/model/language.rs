@@ -0,0 +1,62 @@
+use serde::{Deserialize, Serialize};
+
+/// Whether to remove stop words ("the", "a", ...) from queries.
+/// See https://www.algolia.com/doc/api-reference/api-parameters/removeStopWords/
+///
+/// Serialized as `true`, `false`, or a list of ISO language codes, both as a setting and as a query parameter.
+#[derive(Serialize, Deserialize, Debug, Clone, Eq, PartialEq)]
+#[serde(from = "Repr", into = "Repr")]
+pub enum RemoveStopWords {
+    /// Remove the stop words of every supported language.
+    All,
+    Disabled,
+    /// Only remove the stop words of these languages (e.g. `"en"`, `"fr"`).
+    Languages(Vec<String>),
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(untagged)]
+enum Repr {
+    Bool(bool),
+    Languages(Vec<String>),
+}
+
+impl From<Repr> for RemoveStopWords {
+    fn from(repr: Repr) -> Self {
+        match repr {
+            Repr::Bool(true) => Self::All,
+            Repr::Bool(false) => Self::Disabled,
+            Repr::Languages(languages) => Self::Languages(languages),
+        }
+    }
+}
+
+impl From<RemoveStopWords> for Repr {
+    fn from(it: RemoveStopWords) -> Self {
+        match it {
+            RemoveStopWords::All => Self::Bool(true),
+            RemoveStopWords::Disabled => Self::Bool(false),
+            RemoveStopWords::Languages(languages) => Self::Languages(languages),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::RemoveStopWords;
+
+    #[test]
+    fn remove_stop_words_round_trip() {
+        let values = vec![
+            RemoveStopWords::All,
+            RemoveStopWords::Disabled,
+            RemoveStopWords::Languages(vec!["en".to_owned(), "fr".to_owned()]),
+        ];
+
+        let json = serde_json::to_string(&values).unwrap();
+        assert_eq!(json, r#"[true,false,["en","fr"]]"#);
+
+        let parsed: Vec<RemoveStopWords> = serde_json::from_str(&json).unwrap();
+        assert_eq!(parsed, values);
+    }
+}
@@ -0,0 +1,239 @@
+use serde::{Deserialize, Serialize};
+
+/// How a rule's `pattern` has to match the query.
+/// See https://www.algolia.com/doc/api-reference/api-methods/save-rule/#method-param-anchoring
+#[derive(Serialize, Deserialize, Debug, Copy, Clone, Eq, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub enum Anchoring {
+    /// The query must be exactly the pattern.
+    Is,
+    /// The query must start with the pattern.
+    StartsWith,
+    /// The query must end with the pattern.
+    EndsWith,
+    /// The query must contain the pattern.
+    Contains,
+}
+
+/// The condition that triggers a Query Rule.
+///
+/// Use `Condition::build` to get one, which makes sure that the fields are coherent with each other.
+#[derive(Serialize, Deserialize, Debug, Clone, Eq, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct Condition {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pattern: Option<String>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    anchoring: Option<Anchoring>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    alternatives: Option<bool>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    context: Option<String>,
+}
+
+impl Condition {
+    pub fn build() -> RuleConditionBuilder {
+        RuleConditionBuilder::default()
+    }
+
+    pub fn pattern(&self) -> Option<&str> {
+        self.pattern.as_deref()
+    }
+
+    pub fn anchoring(&self) -> Option<Anchoring> {
+        self.anchoring
+    }
+
+    pub fn alternatives(&self) -> Option<bool> {
+        self.alternatives
+    }
+
+    pub fn context(&self) -> Option<&str> {
+        self.context.as_deref()
+    }
+}
+
+/// A Query Rule.
+/// See https://www.algolia.com/doc/api-reference/api-methods/save-rule/
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct Rule {
+    #[serde(rename = "objectID")]
+    pub object_id: String,
+
+    /// The rule applies if any of these conditions match, a rule without conditions always applies.
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub conditions: Vec<Condition>,
+
+    pub consequence: Consequence,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub enabled: Option<bool>,
+}
+
+/// What a rule does when it applies.
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct Consequence {
+    /// Search parameters to add to the query.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub params: Option<serde_json::Map<String, serde_json::Value>>,
+
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub promote: Vec<Promote>,
+
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub hide: Vec<Hide>,
+
+    /// Custom JSON returned in the `userData` of the search response.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub user_data: Option<serde_json::Value>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Eq, PartialEq)]
+pub struct Promote {
+    #[serde(rename = "objectID")]
+    pub object_id: String,
+
+    /// Position to promote the object to, starting at 0.
+    pub position: usize,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Eq, PartialEq)]
+pub struct Hide {
+    #[serde(rename = "objectID")]
+    pub object_id: String,
+}
+
+#[derive(Debug, thiserror::Error, Copy, Clone, Eq, PartialEq)]
+pub enum InvalidCondition {
+    #[error("a `pattern` needs an `anchoring`")]
+    MissingAnchoring,
+
+    #[error("an `anchoring` needs a `pattern`")]
+    MissingPattern,
+
+    #[error("`alternatives` only applies to conditions with a `pattern`")]
+    AlternativesWithoutPattern,
+
+    #[error("a condition needs at least a `pattern` or a `context`")]
+    Empty,
+}
+
+#[derive(Default, Debug, Clone)]
+pub struct RuleConditionBuilder {
+    pattern: Option<String>,
+    anchoring: Option<Anchoring>,
+    alternatives: Option<bool>,
+    context: Option<String>,
+}
+
+impl RuleConditionBuilder {
+    /// The query pattern, facet placeholders (`{facet:brand}`) are allowed.
+    /// An empty pattern with `Anchoring::Is` matches empty queries.
+    pub fn pattern(mut self, pattern: String) -> Self {
+        self.pattern = Some(pattern);
+        self
+    }
+
+    pub fn anchoring(mut self, anchoring: Anchoring) -> Self {
+        self.anchoring = Some(anchoring);
+        self
+    }
+
+    /// Shorthand for `pattern` and `anchoring`.
+    pub fn matching(self, pattern: String, anchoring: Anchoring) -> Self {
+        self.pattern(pattern).anchoring(anchoring)
+    }
+
+    /// Whether the pattern matches on plurals, synonyms, and typos.
+    pub fn alternatives(mut self, alternatives: bool) -> Self {
+        self.alternatives = Some(alternatives);
+        self
+    }
+
+    /// Only trigger the rule when the search has this `ruleContexts` entry.
+    pub fn context(mut self, context: String) -> Self {
+        self.context = Some(context);
+        self
+    }
+
+    pub fn finish(self) -> Result<Condition, InvalidCondition> {
+        match (&self.pattern, self.anchoring) {
+            (Some(_), None) => return Err(InvalidCondition::MissingAnchoring),
+            (None, Some(_)) => return Err(InvalidCondition::MissingPattern),
+            (None, None) if self.alternatives.is_some() => {
+                return Err(InvalidCondition::AlternativesWithoutPattern)
+            }
+            (None, None) if self.context.is_none() => return Err(InvalidCondition::Empty),
+            _ => {}
+        }
+
+        Ok(Condition {
+            pattern: self.pattern,
+            anchoring: self.anchoring,
+            alternatives: self.alternatives,
+            context: self.context,
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{Anchoring, Condition, InvalidCondition};
+
+    #[test]
+    fn conditions() {
+        insta::assert_json_snapshot!(vec![
+            Condition::build()
+                .matching("shoes".to_owned(), Anchoring::Contains)
+                .alternatives(true)
+                .finish()
+                .unwrap(),
+            Condition::build()
+                .matching("{facet:brand}".to_owned(), Anchoring::StartsWith)
+                .context("mobile".to_owned())
+                .finish()
+                .unwrap(),
+            Condition::build()
+                .context("summer-sale".to_owned())
+                .finish()
+                .unwrap(),
+        ])
+    }
+
+    #[test]
+    fn incoherent_conditions() {
+        assert_eq!(
+            Condition::build().pattern("shoes".to_owned()).finish(),
+            Err(InvalidCondition::MissingAnchoring)
+        );
+
+        assert_eq!(
+            Condition::build()
+                .anchoring(Anchoring::Is)
+                .context("mobile".to_owned())
+                .finish(),
+            Err(InvalidCondition::MissingPattern)
+        );
+
+        assert_eq!(
+            Condition::build()
+                .alternatives(true)
+                .context("mobile".to_owned())
+                .finish(),
+            Err(InvalidCondition::AlternativesWithoutPattern)
+        );
+
+        assert_eq!(Condition::build().finish(), Err(InvalidCondition::Empty));
+    }
+}
@@ -0,0 +1,79 @@
+use serde::{Deserialize, Serialize};
+
+/// A single entry in an index's synonyms dictionary.
+/// See https://www.algolia.com/doc/api-reference/api-methods/save-synonym/
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(tag = "type", rename_all = "camelCase")]
+pub enum Synonym {
+    /// Multi-way synonyms, every word is a synonym of every other word.
+    Synonym {
+        #[serde(rename = "objectID")]
+        object_id: String,
+        synonyms: Vec<String>,
+    },
+    /// `input` is replaced by any of `synonyms`, but not the other way around.
+    OneWaySynonym {
+        #[serde(rename = "objectID")]
+        object_id: String,
+        input: String,
+        synonyms: Vec<String>,
+    },
+    /// `corrections` of `word` are considered as one typo.
+    AltCorrection1 {
+        #[serde(rename = "objectID")]
+        object_id: String,
+        word: String,
+        corrections: Vec<String>,
+    },
+    /// `corrections` of `word` are considered as two typos.
+    AltCorrection2 {
+        #[serde(rename = "objectID")]
+        object_id: String,
+        word: String,
+        corrections: Vec<String>,
+    },
+    /// A placeholder token (`<name>`) in records, matched by any of `replacements`.
+    Placeholder {
+        #[serde(rename = "objectID")]
+        object_id: String,
+        placeholder: String,
+        replacements: Vec<String>,
+    },
+}
+
+impl Synonym {
+    pub fn object_id(&self) -> &str {
+        match self {
+            Self::Synonym { object_id, .. }
+            | Self::OneWaySynonym { object_id, .. }
+            | Self::AltCorrection1 { object_id, .. }
+            | Self::AltCorrection2 { object_id, .. }
+            | Self::Placeholder { object_id, .. } => object_id,
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::Synonym;
+
+    #[test]
+    fn synonyms() {
+        insta::assert_json_snapshot!(vec![
+            Synonym::Synonym {
+                object_id: "a".to_owned(),
+                synonyms: vec!["car".to_owned(), "vehicle".to_owned()],
+            },
+            Synonym::OneWaySynonym {
+                object_id: "b".to_owned(),
+                input: "phone".to_owned(),
+                synonyms: vec!["iphone".to_owned()],
+            },
+            Synonym::Placeholder {
+                object_id: "c".to_owned(),
+                placeholder: "<street>".to_owned(),
+                replacements: vec!["street".to_owned(), "st".to_owned()],
+            },
+        ])
+    }
+}
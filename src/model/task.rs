@@ -1,13 +1,19 @@
 use serde::{Deserialize, Serialize};
+use std::time::Duration;
 
 #[derive(Serialize, Deserialize, Debug, Copy, Clone, Eq, PartialEq)]
 pub struct TaskId(pub(crate) u64);
 
+/// The status of a task.
+///
+/// There is no "failed" status: a task either gets published eventually, or the operation
+/// was rejected when it was sent (and there's no task to wait for).
 #[derive(Deserialize, Debug, Eq, PartialEq, Copy, Clone)]
 #[serde(rename_all = "camelCase")]
 #[non_exhaustive]
 pub enum TaskStatus {
     Published,
+    /// The task is still being processed, keep waiting.
     NotPublished,
 }
 
@@ -16,3 +22,28 @@ impl TaskStatus {
         matches!(self, Self::Published)
     }
 }
+
+/// How `Client::wait_for_tasks_with` polls the status of tasks.
+///
+/// The default matches the official clients' `waitTask`: up to 50 checks,
+/// with a delay doubling from 100ms up to 5s between them.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub struct WaitTaskOptions {
+    /// How many times the tasks are checked (at least once) before giving up
+    /// with `Error::TaskNotPublished`.
+    pub max_retries: usize,
+
+    pub initial_delay: Duration,
+
+    pub max_delay: Duration,
+}
+
+impl Default for WaitTaskOptions {
+    fn default() -> Self {
+        Self {
+            max_retries: 50,
+            initial_delay: Duration::from_millis(100),
+            max_delay: Duration::from_secs(5),
+        }
+    }
+}
@@ -0,0 +1,31 @@
+use serde::Serialize;
+
+/// Whether typos are allowed.
+/// See https://www.algolia.com/doc/api-reference/api-parameters/typoTolerance/
+///
+/// Serialized as a boolean or a string, both as a setting and as a query parameter.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum TypoTolerance {
+    /// `true`, the default.
+    Enabled,
+    /// `false`.
+    Disabled,
+    /// `"min"`: only return the hits with the lowest number of typos.
+    Min,
+    /// `"strict"`: only return the hits with the two lowest numbers of typos.
+    Strict,
+}
+
+impl Serialize for TypoTolerance {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        match self {
+            Self::Enabled => serializer.serialize_bool(true),
+            Self::Disabled => serializer.serialize_bool(false),
+            Self::Min => serializer.serialize_str("min"),
+            Self::Strict => serializer.serialize_str("strict"),
+        }
+    }
+}
@@ -0,0 +1,133 @@
+//! Types for the Recommend API, see `Client::get_recommendations`.
+//! See https://www.algolia.com/doc/rest-api/recommend/
+
+use crate::response::FlattenEmpty;
+use serde::{Deserialize, Serialize};
+
+/// The recommendation model to use, which must have been trained on the index.
+#[derive(Serialize, Debug, Copy, Clone, Eq, PartialEq)]
+#[serde(rename_all = "kebab-case")]
+pub enum RecommendModel {
+    /// Objects similar to `object_id`.
+    RelatedProducts,
+    /// Objects frequently bought along with `object_id`.
+    BoughtTogether,
+    /// Objects trending in the whole index, doesn't take an `object_id`.
+    TrendingItems,
+}
+
+/// A single request of `Client::get_recommendations`.
+#[derive(Serialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct RecommendationsQuery {
+    pub index_name: String,
+
+    pub model: RecommendModel,
+
+    /// The object to get recommendations for, required by every model except `TrendingItems`.
+    #[serde(rename = "objectID")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub object_id: Option<String>,
+
+    /// The minimum score (from 0 to 100) of the recommendations.
+    pub threshold: u8,
+
+    /// Defaults to (and is at most) 30.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_recommendations: Option<u32>,
+}
+
+impl RecommendationsQuery {
+    /// Recommendations from `model` for `object_id`, with a `threshold` of 0 (all of them).
+    pub fn new(index_name: String, model: RecommendModel, object_id: String) -> Self {
+        Self {
+            index_name,
+            model,
+            object_id: Some(object_id),
+            threshold: 0,
+            max_recommendations: None,
+        }
+    }
+
+    /// The trending objects of the whole index, with a `threshold` of 0 (all of them).
+    pub fn trending(index_name: String) -> Self {
+        Self {
+            index_name,
+            model: RecommendModel::TrendingItems,
+            object_id: None,
+            threshold: 0,
+            max_recommendations: None,
+        }
+    }
+}
+
+/// The recommendations for a `RecommendationsQuery`.
+///
+/// Only `hits` is always there, the search fields (`nbHits`, `params`, ...) that algolia
+/// may add aren't meaningful for recommendations.
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct RecommendResponse<T = FlattenEmpty> {
+    pub hits: Vec<RecommendHit<T>>,
+
+    #[serde(rename = "processingTimeMS")]
+    pub processing_time_ms: Option<usize>,
+}
+
+/// A recommended object.
+#[derive(Deserialize, Debug)]
+pub struct RecommendHit<T> {
+    #[serde(rename = "objectID")]
+    pub object_id: String,
+
+    /// How confident the model is in this recommendation, from 0 to 100.
+    #[serde(rename = "_score")]
+    pub score: Option<f64>,
+
+    #[serde(flatten)]
+    pub inner: T,
+}
+
+#[cfg(test)]
+mod test {
+    use super::{RecommendModel, RecommendResponse, RecommendationsQuery};
+
+    #[test]
+    fn recommendations_queries() {
+        insta::assert_json_snapshot!(vec![
+            RecommendationsQuery {
+                threshold: 50,
+                max_recommendations: Some(10),
+                ..RecommendationsQuery::new(
+                    "products".to_owned(),
+                    RecommendModel::BoughtTogether,
+                    "42".to_owned()
+                )
+            },
+            RecommendationsQuery::trending("products".to_owned()),
+        ])
+    }
+
+    #[test]
+    fn recommend_response() {
+        #[derive(serde::Deserialize)]
+        struct Product {
+            name: String,
+        }
+
+        let resp: RecommendResponse<Product> = serde_json::from_value(serde_json::json!({
+            "hits": [
+                { "objectID": "a", "_score": 32.72, "name": "shoes" },
+                { "objectID": "b", "_score": 12.5, "name": "socks" },
+            ],
+            "processingTimeMS": 2,
+        }))
+        .unwrap();
+
+        assert_eq!(resp.hits.len(), 2);
+        assert_eq!(resp.hits[0].object_id, "a");
+        assert_eq!(resp.hits[0].score, Some(32.72));
+        assert_eq!(resp.hits[1].inner.name, "socks");
+        assert_eq!(resp.processing_time_ms, Some(2));
+    }
+}
@@ -1,11 +1,17 @@
 use crate::{
     filter::{EmptyFilter, Filterable},
-    model::attribute::{FacetAttribute, SearchableAttributes},
+    model::{
+        attribute::{FacetAttribute, NumericAttribute, SearchableAttributes},
+        geo::{AroundRadius, BoundingBox, Polygon},
+        language::RemoveStopWords,
+        typo::TypoTolerance,
+    },
 };
 
 use crate::filter::{CommonFilter, CommonFilterKind};
 use chrono::{DateTime, Utc};
 use serde::{ser::SerializeMap, Serialize};
+use std::{collections::HashMap, fmt};
 
 /// Perform multiple write operations in a single API call.
 /// In order to reduce the amount of time spent on network round trips, you can perform multiple write operations at once.
@@ -25,8 +31,12 @@ pub enum UnimplementedOperation {}
 #[serde(tag = "action", content = "body")]
 #[serde(rename_all = "camelCase")]
 pub enum BatchWriteRequest {
-    /// Unimplemented.
-    AddObject(UnimplementedOperation),
+    /// Add an object, algolia generates its object ID.
+    /// Equivalent to Add a new object.
+    AddObject {
+        #[serde(flatten)]
+        body: serde_json::Map<String, serde_json::Value>,
+    },
     /// Add or replace an existing object.
     /// You must set the `object_id` attribute to indicate the object to update.
     /// Equivalent to Add/update an object by ID.
@@ -53,8 +63,12 @@ pub enum BatchWriteRequest {
         #[serde(rename = "objectID")]
         object_id: String,
     },
-    /// Unimplemented.
-    DeleteObject(UnimplementedOperation),
+    /// Delete an object.
+    /// Equivalent to Delete an object.
+    DeleteObject {
+        #[serde(rename = "objectID")]
+        object_id: String,
+    },
 
     /// Unimplemented.
     Delete(UnimplementedOperation),
@@ -74,28 +88,130 @@ fn test() {
     .unwrap());
 }
 
-#[derive(Default)]
+/// The parameters of a search.
+///
+/// `filters`, `facet_filters`, `numeric_filters` and `tag_filters` can all be used at once, algolia ANDs them:
+/// a hit has to match each of them, not just one.
 pub struct SearchQuery<'a, T: CommonFilterKind, U: Filterable = EmptyFilter> {
     /// The text to search in the index.
     pub query: Option<&'a str>,
 
     /// Specify the page to retrieve.
+    ///
+    /// Pages past the index's `paginationLimitedTo` (1000 hits by default) are empty,
+    /// see `SearchResponse::has_more_within_limit`.
     pub page: Option<u32>,
 
     /// Specify the number of hits to retrieve per page.
     pub hits_per_page: Option<u16>,
 
+    /// Retrieve hits starting at this position (starting at 0), instead of using `page`.
+    /// Must be used with `length`.
+    pub offset: Option<u32>,
+
+    /// The number of hits to retrieve with `offset`.
+    pub length: Option<u32>,
+
     /// Search filters.
     pub filters: Option<U>,
 
+    /// Only retrieve these attributes of the hits, instead of the whole objects.
+    pub attributes_to_retrieve: Option<Vec<String>>,
+
+    /// Facets to retrieve the counts of, `*` retrieves all of them.
+    pub facets: Option<Vec<String>>,
+
+    /// Facet filters as an array, records must match at least one entry of each inner `Vec`.
+    /// See `FacetFiltersBuilder`.
+    pub facet_filters: Option<Vec<Vec<String>>>,
+
+    /// Numeric filters like `price<100` as an array, with the same semantics as `facet_filters`.
+    pub numeric_filters: Option<Vec<Vec<String>>>,
+
+    /// Filters on the `_tags` of the records, with the same semantics as `facet_filters`.
+    pub tag_filters: Option<Vec<Vec<String>>>,
+
     /// Optional filters, passed as a part of the request body.
     pub optional_filters: Option<Vec<CommonFilter<T>>>,
 
-    /// Retrieve detailed ranking information.
-    pub get_ranking_info: bool,
+    /// Retrieve detailed ranking information, left to the index's default when `None`.
+    pub get_ranking_info: Option<bool>,
 
-    /// Whether to sum the scores of scored Or filters
+    /// Whether to sum the scores of scored Or filters, instead of keeping the highest one.
+    ///
+    /// This only changes anything for queries with scored filters: `optional_filters` (which have a score of 1 by default),
+    /// or `ScoredFacetFilter`s in `filters`. `Client::search` debug-asserts that there are some.
+    /// See https://www.algolia.com/doc/api-reference/api-parameters/sumOrFiltersScores/
     pub sum_or_filters_scores: bool,
+
+    /// Return a `queryID` with the response, to be used with Insights events.
+    pub click_analytics: bool,
+
+    /// Only return hits located in one of these areas, see `BoundingBox::around` for building one from a point and a radius.
+    pub inside_bounding_box: Option<Vec<BoundingBox>>,
+
+    /// Only return hits located in one of these polygons.
+    pub inside_polygon: Option<Vec<Polygon>>,
+
+    /// Search around this (latitude, longitude), ranking hits by distance.
+    pub around_lat_lng: Option<(f64, f64)>,
+
+    /// Only return hits within this distance of `around_lat_lng`,
+    /// by default algolia picks a radius based on the density of the area.
+    pub around_radius: Option<AroundRadius>,
+
+    /// Override the index's `removeStopWords` setting, e.g. to disable it for exact-match searches.
+    pub remove_stop_words: Option<RemoveStopWords>,
+
+    /// Override the index's `typoTolerance` setting.
+    pub typo_tolerance: Option<TypoTolerance>,
+
+    /// Whether to expand the query with the index's synonyms, defaults to `true`.
+    pub synonyms: Option<bool>,
+
+    /// Whether to highlight the synonym that matched (`true`) or the query words (`false`), defaults to `false`.
+    pub replace_synonyms_in_highlight: Option<bool>,
+
+    /// Tags to segment this search in analytics, see `SearchQuery::add_analytics_tag` which validates them.
+    pub analytics_tags: Option<Vec<String>>,
+
+    /// Sent as the `X-Algolia-UserToken` header (not as a search parameter),
+    /// overriding the client's default set with `Client::with_user_token`.
+    pub user_token: Option<&'a str>,
+}
+
+// can't use the derive macro, it would require `T: Default` and `U: Default`,
+// which filters like `ScoredFacetFilter` have no reason to implement.
+impl<T: CommonFilterKind, U: Filterable> Default for SearchQuery<'_, T, U> {
+    fn default() -> Self {
+        Self {
+            query: None,
+            page: None,
+            hits_per_page: None,
+            offset: None,
+            length: None,
+            filters: None,
+            attributes_to_retrieve: None,
+            facets: None,
+            facet_filters: None,
+            numeric_filters: None,
+            tag_filters: None,
+            optional_filters: None,
+            get_ranking_info: None,
+            sum_or_filters_scores: false,
+            click_analytics: false,
+            inside_bounding_box: None,
+            inside_polygon: None,
+            around_lat_lng: None,
+            around_radius: None,
+            remove_stop_words: None,
+            typo_tolerance: None,
+            synonyms: None,
+            replace_synonyms_in_highlight: None,
+            analytics_tags: None,
+            user_token: None,
+        }
+    }
 }
 
 // can't use the derive macro due to a lack of T: Serialize bound
@@ -118,13 +234,49 @@ impl<T: CommonFilterKind, U: Filterable> serde::Serialize for SearchQuery<'_, T,
             map.serialize_entry("hitsPerPage", &hits_per_page)?;
         }
 
+        if let Some(offset) = self.offset {
+            map.serialize_entry("offset", &offset)?;
+        }
+
+        if let Some(length) = self.length {
+            map.serialize_entry("length", &length)?;
+        }
+
         if let Some(filters) = &self.filters {
             map.serialize_entry("filters", &format_args!("{}", filters))?;
         }
 
-        // algolia will guess this to be true by default.
-        if !self.get_ranking_info {
-            map.serialize_entry("getRankingInfo", &false)?;
+        if let Some(attributes) = &self.attributes_to_retrieve {
+            let attributes =
+                serde_json::to_string(attributes).map_err(serde::ser::Error::custom)?;
+            map.serialize_entry("attributesToRetrieve", &attributes)?;
+        }
+
+        if let Some(facets) = self.facets.as_deref().filter(|it| !it.is_empty()) {
+            let facets = serde_json::to_string(facets).map_err(serde::ser::Error::custom)?;
+            map.serialize_entry("facets", &facets)?;
+        }
+
+        if let Some(facet_filters) = self.facet_filters.as_deref().filter(|it| !it.is_empty()) {
+            let facet_filters =
+                serde_json::to_string(facet_filters).map_err(serde::ser::Error::custom)?;
+            map.serialize_entry("facetFilters", &facet_filters)?;
+        }
+
+        if let Some(numeric_filters) = self.numeric_filters.as_deref().filter(|it| !it.is_empty()) {
+            let numeric_filters =
+                serde_json::to_string(numeric_filters).map_err(serde::ser::Error::custom)?;
+            map.serialize_entry("numericFilters", &numeric_filters)?;
+        }
+
+        if let Some(tag_filters) = self.tag_filters.as_deref().filter(|it| !it.is_empty()) {
+            let tag_filters =
+                serde_json::to_string(tag_filters).map_err(serde::ser::Error::custom)?;
+            map.serialize_entry("tagFilters", &tag_filters)?;
+        }
+
+        if let Some(get_ranking_info) = self.get_ranking_info {
+            map.serialize_entry("getRankingInfo", &get_ranking_info)?;
         }
 
         // algolia will guess this to the false by default.
@@ -132,19 +284,173 @@ impl<T: CommonFilterKind, U: Filterable> serde::Serialize for SearchQuery<'_, T,
             map.serialize_entry("sumOrFiltersScores", &true)?;
         }
 
+        if self.click_analytics {
+            map.serialize_entry("clickAnalytics", &true)?;
+        }
+
+        if let Some(boxes) = self
+            .inside_bounding_box
+            .as_deref()
+            .filter(|it| !it.is_empty())
+        {
+            let boxes = serde_json::to_string(boxes).map_err(serde::ser::Error::custom)?;
+            map.serialize_entry("insideBoundingBox", &boxes)?;
+        }
+
+        if let Some(polygons) = self.inside_polygon.as_deref().filter(|it| !it.is_empty()) {
+            let polygons = serde_json::to_string(polygons).map_err(serde::ser::Error::custom)?;
+            map.serialize_entry("insidePolygon", &polygons)?;
+        }
+
+        if let Some((lat, lng)) = self.around_lat_lng {
+            map.serialize_entry("aroundLatLng", &format_args!("{},{}", lat, lng))?;
+        }
+
+        // `"all"` or a number of meters.
+        if let Some(around_radius) = &self.around_radius {
+            map.serialize_entry("aroundRadius", around_radius)?;
+        }
+
+        if let Some(remove_stop_words) = &self.remove_stop_words {
+            // `true`, `false`, or a JSON array of languages.
+            let remove_stop_words =
+                serde_json::to_string(remove_stop_words).map_err(serde::ser::Error::custom)?;
+            map.serialize_entry("removeStopWords", &remove_stop_words)?;
+        }
+
+        // a bare `true`/`false`, or `min`/`strict`.
+        if let Some(typo_tolerance) = &self.typo_tolerance {
+            map.serialize_entry("typoTolerance", typo_tolerance)?;
+        }
+
+        if let Some(synonyms) = self.synonyms {
+            map.serialize_entry("synonyms", &synonyms)?;
+        }
+
+        if let Some(replace_synonyms_in_highlight) = self.replace_synonyms_in_highlight {
+            map.serialize_entry("replaceSynonymsInHighlight", &replace_synonyms_in_highlight)?;
+        }
+
+        if let Some(tags) = self.analytics_tags.as_deref().filter(|it| !it.is_empty()) {
+            let tags = serde_json::to_string(tags).map_err(serde::ser::Error::custom)?;
+            map.serialize_entry("analyticsTags", &tags)?;
+        }
+
         map.end()
     }
 }
 
+#[derive(Debug, thiserror::Error, Clone, PartialEq, Eq)]
+pub enum InvalidAnalyticsTag {
+    #[error("analytics tags can't be empty")]
+    Empty,
+
+    #[error("analytics tags are limited to {} characters", SearchQuery::<String>::MAX_ANALYTICS_TAG_LEN)]
+    TooLong,
+
+    #[error("analytics tags can't contain control characters")]
+    ControlCharacter,
+
+    #[error("a search is limited to {} analytics tags", SearchQuery::<String>::MAX_ANALYTICS_TAGS)]
+    TooMany,
+}
+
+/// A single object to retrieve with `Client::get_objects`.
+#[derive(serde::Serialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct GetObjectRequest {
+    pub index_name: String,
+
+    #[serde(rename = "objectID")]
+    pub object_id: String,
+
+    /// Only retrieve these attributes, instead of the whole object.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub attributes_to_retrieve: Option<Vec<String>>,
+}
+
+impl GetObjectRequest {
+    pub fn new(index_name: String, object_id: String) -> Self {
+        Self {
+            index_name,
+            object_id,
+            attributes_to_retrieve: None,
+        }
+    }
+}
+
+impl<T: CommonFilterKind, U: Filterable> SearchQuery<'_, T, U> {
+    /// The most analytics tags a search can have.
+    /// See https://www.algolia.com/doc/api-reference/api-parameters/analyticsTags/
+    pub const MAX_ANALYTICS_TAGS: usize = 10;
+
+    /// The longest an analytics tag can be, in characters.
+    /// See https://www.algolia.com/doc/api-reference/api-parameters/analyticsTags/
+    pub const MAX_ANALYTICS_TAG_LEN: usize = 100;
+
+    /// Add `tag` to `analytics_tags`, checking it against the limits of the analytics API
+    /// instead of having the search rejected there. `analytics_tags` is left as is on error.
+    pub fn add_analytics_tag(&mut self, tag: String) -> Result<(), InvalidAnalyticsTag> {
+        if tag.is_empty() {
+            return Err(InvalidAnalyticsTag::Empty);
+        }
+
+        if tag.chars().count() > Self::MAX_ANALYTICS_TAG_LEN {
+            return Err(InvalidAnalyticsTag::TooLong);
+        }
+
+        if tag.chars().any(char::is_control) {
+            return Err(InvalidAnalyticsTag::ControlCharacter);
+        }
+
+        let tags = self.analytics_tags.get_or_insert_with(Vec::new);
+        if tags.len() >= Self::MAX_ANALYTICS_TAGS {
+            return Err(InvalidAnalyticsTag::TooMany);
+        }
+
+        tags.push(tag);
+        Ok(())
+    }
+
+    /// Whether `sum_or_filters_scores` has anything to sum.
+    pub(crate) fn has_scored_filters(&self) -> bool {
+        let has_optional_filters = self
+            .optional_filters
+            .as_deref()
+            .is_some_and(|it| !it.is_empty());
+
+        has_optional_filters || self.filters.as_ref().is_some_and(|it| it.has_score())
+    }
+
+    /// The urlencoded `params` string sent by `Client::search`, handy for logging the effective query.
+    ///
+    /// Note that `optional_filters` isn't part of it, it's sent alongside `params` in the request body.
+    pub fn to_params_string(&self) -> String {
+        serde_urlencoded::to_string(self).expect("request should be serializable")
+    }
+}
+
+/// Same as `SearchQuery::to_params_string`.
+impl<T: CommonFilterKind, U: Filterable> fmt::Display for SearchQuery<'_, T, U> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&serde_urlencoded::to_string(self).map_err(|_| fmt::Error)?)
+    }
+}
+
 #[derive(serde::Serialize)]
 #[serde(rename_all = "camelCase")]
 pub struct PartialUpdateQuery {
     /// When true, a partial update on a nonexistent object will create the object, assuming an empty object as the basis.
     /// When false, a partial update on a nonexistent object will be ignored.
-    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    // algolia defaults to `true`, so it's the value that doesn't need to be sent.
+    #[serde(skip_serializing_if = "is_true")]
     pub create_if_not_exists: bool,
 }
 
+fn is_true(b: &bool) -> bool {
+    *b
+}
+
 impl Default for PartialUpdateQuery {
     fn default() -> Self {
         Self {
@@ -153,6 +459,144 @@ impl Default for PartialUpdateQuery {
     }
 }
 
+/// A built-in operation applied to an attribute by a partial update, instead of replacing its value.
+/// See https://www.algolia.com/doc/api-reference/api-methods/partial-update-objects/#update-operations
+#[derive(serde::Serialize, Debug, Clone, PartialEq)]
+#[serde(tag = "_operation", content = "value")]
+pub enum PartialUpdateOperation {
+    Increment(i64),
+    Decrement(i64),
+    /// Append to an array attribute.
+    Add(serde_json::Value),
+    /// Remove all the occurrences of a value from an array attribute.
+    Remove(serde_json::Value),
+    /// Append to an array attribute, unless it's already there.
+    AddUnique(serde_json::Value),
+    /// Increment, but only if the current value is the given one (or if there's none, when it's 0).
+    IncrementFrom(i64),
+    /// Set to the given value, but only if it's greater than the current one.
+    IncrementSet(i64),
+}
+
+/// The body of a partial update: new values of attributes, or operations on them.
+///
+/// # Examples
+/// ```
+/// use algolia::request::PartialUpdateBody;
+///
+/// let body = PartialUpdateBody::new()
+///     .decrement("stock".to_owned(), 1)
+///     .set("lastSoldAt".to_owned(), "2021-08-06".into());
+/// ```
+#[derive(serde::Serialize, Debug, Clone, Default, PartialEq)]
+#[serde(transparent)]
+pub struct PartialUpdateBody(serde_json::Map<String, serde_json::Value>);
+
+impl PartialUpdateBody {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Replace the value of `attribute`.
+    pub fn set(mut self, attribute: String, value: serde_json::Value) -> Self {
+        self.0.insert(attribute, value);
+        self
+    }
+
+    pub fn operation(self, attribute: String, operation: PartialUpdateOperation) -> Self {
+        // can't fail, there's nothing but strings and JSON values in there.
+        let operation = serde_json::to_value(operation).unwrap();
+        self.set(attribute, operation)
+    }
+
+    pub fn increment(self, attribute: String, by: i64) -> Self {
+        self.operation(attribute, PartialUpdateOperation::Increment(by))
+    }
+
+    pub fn decrement(self, attribute: String, by: i64) -> Self {
+        self.operation(attribute, PartialUpdateOperation::Decrement(by))
+    }
+
+    /// The batch operation applying this update to `object_id`.
+    pub fn into_batch_request(
+        self,
+        object_id: String,
+        create_if_not_exists: bool,
+    ) -> BatchWriteRequest {
+        if create_if_not_exists {
+            BatchWriteRequest::PartialUpdateObject {
+                body: self.0,
+                object_id,
+            }
+        } else {
+            BatchWriteRequest::PartialUpdateObjectNoCreate {
+                body: self.0,
+                object_id,
+            }
+        }
+    }
+}
+
+/// What `Client::copy_index` copies, everything when empty.
+#[derive(serde::Serialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum CopyScope {
+    Settings,
+    Synonyms,
+    Rules,
+}
+
+/// The body of a copy or move of an index.
+#[derive(serde::Serialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct IndexOperation<'a> {
+    pub operation: &'static str,
+    pub destination: &'a str,
+    #[serde(skip_serializing_if = "<[_]>::is_empty")]
+    pub scope: &'a [CopyScope],
+}
+
+impl<'a> IndexOperation<'a> {
+    pub(crate) fn copy(destination: &'a str, scope: &'a [CopyScope]) -> Self {
+        Self {
+            operation: "copy",
+            destination,
+            scope,
+        }
+    }
+}
+
+/// Query parameters shared by every method writing synonyms or rules.
+#[derive(serde::Serialize, Debug, Default, Clone, Copy)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct ReplicaWriteQuery {
+    /// Also apply the change to the replicas of the index.
+    /// Forgetting it on a primary index leaves its replicas out of sync.
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub forward_to_replicas: bool,
+
+    /// Replace all the synonyms in the index with the ones sent in a batch.
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub replace_existing_synonyms: bool,
+
+    /// Remove all the rules in the index before adding the ones sent in a batch.
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub clear_existing_rules: bool,
+}
+
+impl ReplicaWriteQuery {
+    pub(crate) fn new(forward_to_replicas: bool) -> Self {
+        Self {
+            forward_to_replicas,
+            ..Self::default()
+        }
+    }
+}
+
+/// Settings to apply to an index.
+///
+/// Algolia merges these into the current settings: fields left as `None` aren't sent, and are left unchanged on the index.
+/// So there's no need to fetch the current settings to change a single one, see `SetSettings::patch`.
 #[derive(serde::Serialize, Debug, Default, Clone)]
 #[serde(rename_all = "camelCase")]
 pub struct SetSettings {
@@ -160,6 +604,83 @@ pub struct SetSettings {
     pub searchable_attributes: Option<SearchableAttributes>,
     #[serde(skip_serializing_if = "std::option::Option::is_none")]
     pub attributes_for_faceting: Option<Vec<FacetAttribute>>,
+    #[serde(skip_serializing_if = "std::option::Option::is_none")]
+    pub remove_stop_words: Option<RemoveStopWords>,
+    /// Numeric attributes that can be filtered on, all of them by default.
+    #[serde(skip_serializing_if = "std::option::Option::is_none")]
+    pub numeric_attributes_for_filtering: Option<Vec<NumericAttribute>>,
+    #[serde(skip_serializing_if = "std::option::Option::is_none")]
+    pub typo_tolerance: Option<TypoTolerance>,
+    /// The maximum number of hits that can be paginated through, 1000 by default.
+    #[serde(skip_serializing_if = "std::option::Option::is_none")]
+    pub pagination_limited_to: Option<u32>,
+    /// Replacements for characters the engine doesn't normalize the way you want,
+    /// per script, e.g. `{"default": {"ä": "ae"}}`.
+    #[serde(skip_serializing_if = "std::option::Option::is_none")]
+    pub custom_normalization: Option<HashMap<String, HashMap<String, String>>>,
+}
+
+impl SetSettings {
+    /// Settings that don't change anything, to be filled in with the `with_*` methods.
+    ///
+    /// # Examples
+    /// ```
+    /// use algolia::{model::attribute::{Attribute, FacetAttribute}, request::SetSettings};
+    ///
+    /// // only changes `attributesForFaceting`, everything else is left as-is.
+    /// let settings = SetSettings::patch()
+    ///     .with_attributes_for_faceting(vec![FacetAttribute::new(Attribute("brand".to_owned()))]);
+    /// ```
+    pub fn patch() -> Self {
+        Self::default()
+    }
+
+    pub fn with_searchable_attributes(
+        mut self,
+        searchable_attributes: SearchableAttributes,
+    ) -> Self {
+        self.searchable_attributes = Some(searchable_attributes);
+        self
+    }
+
+    pub fn with_attributes_for_faceting(
+        mut self,
+        attributes_for_faceting: Vec<FacetAttribute>,
+    ) -> Self {
+        self.attributes_for_faceting = Some(attributes_for_faceting);
+        self
+    }
+
+    pub fn with_remove_stop_words(mut self, remove_stop_words: RemoveStopWords) -> Self {
+        self.remove_stop_words = Some(remove_stop_words);
+        self
+    }
+
+    pub fn with_numeric_attributes_for_filtering(
+        mut self,
+        numeric_attributes_for_filtering: Vec<NumericAttribute>,
+    ) -> Self {
+        self.numeric_attributes_for_filtering = Some(numeric_attributes_for_filtering);
+        self
+    }
+
+    pub fn with_typo_tolerance(mut self, typo_tolerance: TypoTolerance) -> Self {
+        self.typo_tolerance = Some(typo_tolerance);
+        self
+    }
+
+    pub fn with_pagination_limited_to(mut self, pagination_limited_to: u32) -> Self {
+        self.pagination_limited_to = Some(pagination_limited_to);
+        self
+    }
+
+    pub fn with_custom_normalization(
+        mut self,
+        custom_normalization: HashMap<String, HashMap<String, String>>,
+    ) -> Self {
+        self.custom_normalization = Some(custom_normalization);
+        self
+    }
 }
 
 #[derive(serde::Serialize, Debug, Clone, Default)]
@@ -206,3 +727,527 @@ mod datetime_timestamp {
         }
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::{
+        BatchWriteRequest, CopyScope, GetObjectRequest, IndexOperation, InvalidAnalyticsTag,
+        PartialUpdateBody, PartialUpdateOperation, PartialUpdateQuery, ReplicaWriteQuery,
+        SearchQuery, SetSettings,
+    };
+    use crate::model::attribute::{Attribute, FacetAttribute, NumericAttribute};
+    use crate::{
+        filter::{
+            AndFilter, CommonFilter, FacetFilter, FacetFilterEntry, FacetFiltersBuilder, OrFilter,
+            ScoredFacetFilter,
+        },
+        model::{
+            geo::{AroundRadius, BoundingBox, Polygon},
+            language::RemoveStopWords,
+            typo::TypoTolerance,
+        },
+    };
+    use std::collections::HashMap;
+
+    #[test]
+    fn empty_settings_patch_nothing() {
+        assert_eq!(serde_json::to_string(&SetSettings::patch()).unwrap(), "{}");
+    }
+
+    #[test]
+    fn settings_patch() {
+        insta::assert_json_snapshot!(SetSettings::patch()
+            .with_attributes_for_faceting(vec![FacetAttribute::new(Attribute("brand".to_owned()))]))
+    }
+
+    #[test]
+    fn replica_write_query() {
+        let query = |it| serde_urlencoded::to_string(it).unwrap();
+
+        assert_eq!(query(ReplicaWriteQuery::new(false)), "");
+        assert_eq!(
+            query(ReplicaWriteQuery::new(true)),
+            "forwardToReplicas=true"
+        );
+
+        assert_eq!(
+            query(ReplicaWriteQuery {
+                replace_existing_synonyms: true,
+                ..ReplicaWriteQuery::new(true)
+            }),
+            "forwardToReplicas=true&replaceExistingSynonyms=true"
+        );
+
+        assert_eq!(
+            query(ReplicaWriteQuery {
+                clear_existing_rules: true,
+                ..ReplicaWriteQuery::new(false)
+            }),
+            "clearExistingRules=true"
+        );
+    }
+
+    #[test]
+    fn get_object_requests() {
+        insta::assert_json_snapshot!(vec![
+            GetObjectRequest::new("products".to_owned(), "1".to_owned()),
+            GetObjectRequest {
+                attributes_to_retrieve: Some(vec!["email".to_owned()]),
+                ..GetObjectRequest::new("users".to_owned(), "42".to_owned())
+            },
+        ])
+    }
+
+    #[test]
+    fn search_query_params_string() {
+        let query = SearchQuery {
+            query: Some("red shoes"),
+            page: Some(2),
+            filters: Some(AndFilter {
+                filters: vec![Box::new(CommonFilter {
+                    invert: false,
+                    filter: FacetFilter {
+                        facet_name: "brand".to_owned(),
+                        value: "acme".to_owned(),
+                    },
+                })],
+            }),
+            ..SearchQuery::<String, _>::default()
+        };
+
+        assert_eq!(
+            query.to_string(),
+            "query=red+shoes&page=2&filters=%22brand%22%3A%22acme%22"
+        );
+        assert_eq!(query.to_string(), query.to_params_string());
+    }
+
+    #[test]
+    fn partial_update_query() {
+        let query = |it| serde_urlencoded::to_string(it).unwrap();
+
+        assert_eq!(query(PartialUpdateQuery::default()), "");
+        assert_eq!(
+            query(PartialUpdateQuery {
+                create_if_not_exists: false
+            }),
+            "createIfNotExists=false"
+        );
+    }
+
+    #[test]
+    fn search_query_inside_bounding_box() {
+        let query = SearchQuery::<String> {
+            inside_bounding_box: Some(vec![
+                BoundingBox([46.0, 6.0, 47.0, 7.5]),
+                BoundingBox([-1.0, -1.0, 1.0, 1.0]),
+            ]),
+            ..SearchQuery::default()
+        };
+
+        assert_eq!(
+            query.to_params_string(),
+            "insideBoundingBox=%5B%5B46.0%2C6.0%2C47.0%2C7.5%5D%2C%5B-1.0%2C-1.0%2C1.0%2C1.0%5D%5D"
+        );
+    }
+
+    #[test]
+    fn search_query_inside_polygon() {
+        let query = SearchQuery::<String> {
+            inside_polygon: Some(vec![Polygon::new(vec![
+                (46.0, 6.0),
+                (47.0, 6.5),
+                (46.5, 7.5),
+            ])
+            .unwrap()]),
+            ..SearchQuery::default()
+        };
+
+        let params =
+            serde_urlencoded::from_str::<Vec<(String, String)>>(&query.to_params_string()).unwrap();
+
+        assert_eq!(
+            params,
+            [(
+                "insidePolygon".to_owned(),
+                "[[46.0,6.0,47.0,6.5,46.5,7.5]]".to_owned()
+            )]
+        );
+    }
+
+    #[test]
+    fn search_query_facet_filters() {
+        let query = SearchQuery::<String> {
+            facet_filters: Some(
+                FacetFiltersBuilder::default()
+                    .or(vec![
+                        FacetFilterEntry::new("brand".to_owned(), "apple".to_owned()),
+                        FacetFilterEntry::new("brand".to_owned(), "samsung".to_owned()),
+                    ])
+                    .and(FacetFilterEntry::negated(
+                        "color".to_owned(),
+                        "red".to_owned(),
+                    ))
+                    .finish(),
+            ),
+            ..SearchQuery::default()
+        };
+
+        let params = query.to_params_string();
+        let (key, value) = serde_urlencoded::from_str::<Vec<(String, String)>>(&params)
+            .unwrap()
+            .pop()
+            .unwrap();
+
+        assert_eq!(key, "facetFilters");
+        assert_eq!(value, r#"[["brand:apple","brand:samsung"],["color:-red"]]"#);
+    }
+
+    #[test]
+    fn search_query_all_filters() {
+        let query = SearchQuery::<String, _> {
+            filters: Some(CommonFilter {
+                invert: false,
+                filter: FacetFilter {
+                    facet_name: "brand".to_owned(),
+                    value: "acme".to_owned(),
+                },
+            }),
+            facet_filters: Some(vec![vec!["color:red".to_owned()]]),
+            numeric_filters: Some(vec![vec!["price<100".to_owned()]]),
+            tag_filters: Some(vec![vec!["new".to_owned()]]),
+            ..SearchQuery::default()
+        };
+
+        let params =
+            serde_urlencoded::from_str::<Vec<(String, String)>>(&query.to_params_string()).unwrap();
+
+        assert_eq!(
+            params,
+            [
+                ("filters".to_owned(), r#""brand":"acme""#.to_owned()),
+                ("facetFilters".to_owned(), r#"[["color:red"]]"#.to_owned()),
+                ("numericFilters".to_owned(), r#"[["price<100"]]"#.to_owned()),
+                ("tagFilters".to_owned(), r#"[["new"]]"#.to_owned()),
+            ]
+        );
+    }
+
+    #[test]
+    fn search_query_tag_filters() {
+        let query = SearchQuery::<String> {
+            tag_filters: Some(vec![
+                vec!["books".to_owned(), "movies".to_owned()],
+                vec!["new".to_owned()],
+            ]),
+            ..SearchQuery::default()
+        };
+
+        assert_eq!(
+            query.to_params_string(),
+            "tagFilters=%5B%5B%22books%22%2C%22movies%22%5D%2C%5B%22new%22%5D%5D"
+        );
+    }
+
+    #[test]
+    fn search_query_sum_or_filters_scores() {
+        let scored = |value: &str, score| CommonFilter {
+            invert: false,
+            filter: ScoredFacetFilter {
+                facet_name: "brand".to_owned(),
+                value: value.to_owned(),
+                score,
+            },
+        };
+
+        let query = SearchQuery::<ScoredFacetFilter> {
+            query: Some("phone"),
+            optional_filters: Some(vec![scored("apple", 2), scored("samsung", 1)]),
+            sum_or_filters_scores: true,
+            ..SearchQuery::default()
+        };
+
+        assert!(query.has_scored_filters());
+        assert_eq!(
+            query.to_params_string(),
+            "query=phone&sumOrFiltersScores=true"
+        );
+
+        let optional_filters = query
+            .optional_filters
+            .iter()
+            .flatten()
+            .map(ToString::to_string)
+            .collect::<Vec<_>>();
+
+        assert_eq!(
+            optional_filters,
+            ["brand:apple<score=2>", "brand:samsung<score=1>"]
+        );
+
+        let unscored = SearchQuery::<String, CommonFilter<String>> {
+            filters: Some(CommonFilter {
+                invert: false,
+                filter: "brand:apple".to_owned(),
+            }),
+            sum_or_filters_scores: true,
+            ..SearchQuery::default()
+        };
+
+        assert!(!unscored.has_scored_filters());
+        assert_eq!(
+            unscored.to_params_string(),
+            "filters=brand%3Aapple&sumOrFiltersScores=true"
+        );
+
+        let scored_filters = SearchQuery::<String, OrFilter<ScoredFacetFilter>> {
+            filters: Some(OrFilter {
+                filters: vec![scored("apple", 2), scored("samsung", 1)],
+            }),
+            ..SearchQuery::default()
+        };
+
+        assert!(scored_filters.has_scored_filters());
+    }
+
+    #[test]
+    fn search_query_remove_stop_words() {
+        let params = |remove_stop_words| {
+            SearchQuery::<String> {
+                remove_stop_words: Some(remove_stop_words),
+                ..SearchQuery::default()
+            }
+            .to_params_string()
+        };
+
+        assert_eq!(params(RemoveStopWords::All), "removeStopWords=true");
+        assert_eq!(params(RemoveStopWords::Disabled), "removeStopWords=false");
+        assert_eq!(
+            params(RemoveStopWords::Languages(vec![
+                "en".to_owned(),
+                "fr".to_owned()
+            ])),
+            "removeStopWords=%5B%22en%22%2C%22fr%22%5D"
+        );
+    }
+
+    #[test]
+    fn search_query_synonyms() {
+        let params = |synonyms, replace_synonyms_in_highlight| {
+            SearchQuery::<String> {
+                synonyms,
+                replace_synonyms_in_highlight,
+                ..SearchQuery::default()
+            }
+            .to_params_string()
+        };
+
+        assert_eq!(params(None, None), "");
+        assert_eq!(params(Some(false), None), "synonyms=false");
+        assert_eq!(
+            params(Some(true), Some(true)),
+            "synonyms=true&replaceSynonymsInHighlight=true"
+        );
+    }
+
+    #[test]
+    fn search_query_around_radius() {
+        let params = |around_radius| {
+            SearchQuery::<String> {
+                around_lat_lng: Some((40.71, -74.01)),
+                around_radius: Some(around_radius),
+                ..SearchQuery::default()
+            }
+            .to_params_string()
+        };
+
+        assert_eq!(
+            params(AroundRadius::All),
+            "aroundLatLng=40.71%2C-74.01&aroundRadius=all"
+        );
+        assert_eq!(
+            params(AroundRadius::Meters(5000)),
+            "aroundLatLng=40.71%2C-74.01&aroundRadius=5000"
+        );
+    }
+
+    const TYPO_TOLERANCES: [(TypoTolerance, &str); 4] = [
+        (TypoTolerance::Enabled, "true"),
+        (TypoTolerance::Disabled, "false"),
+        (TypoTolerance::Min, "\"min\""),
+        (TypoTolerance::Strict, "\"strict\""),
+    ];
+
+    #[test]
+    fn search_query_typo_tolerance() {
+        for &(typo_tolerance, json) in &TYPO_TOLERANCES {
+            let query = SearchQuery::<String> {
+                typo_tolerance: Some(typo_tolerance),
+                ..SearchQuery::default()
+            };
+
+            // urlencoded params have no types, so booleans and strings both end up unquoted.
+            assert_eq!(
+                query.to_params_string(),
+                format!("typoTolerance={}", json.trim_matches('"'))
+            );
+        }
+    }
+
+    #[test]
+    fn settings_typo_tolerance() {
+        for &(typo_tolerance, json) in &TYPO_TOLERANCES {
+            let settings = SetSettings::patch().with_typo_tolerance(typo_tolerance);
+
+            // but the settings are JSON, where `true` must be a boolean, not `"true"`.
+            assert_eq!(
+                serde_json::to_string(&settings).unwrap(),
+                format!(r#"{{"typoTolerance":{}}}"#, json)
+            );
+        }
+    }
+
+    #[test]
+    fn batch_delete_object() {
+        let request = BatchWriteRequest::DeleteObject {
+            object_id: "42".to_owned(),
+        };
+
+        assert_eq!(
+            serde_json::to_value(&request).unwrap(),
+            serde_json::json!({ "action": "deleteObject", "body": { "objectID": "42" } })
+        );
+    }
+
+    #[test]
+    fn search_query_offset_length() {
+        let query = SearchQuery::<String> {
+            offset: Some(100),
+            length: Some(50),
+            ..SearchQuery::default()
+        };
+
+        assert_eq!(query.to_params_string(), "offset=100&length=50");
+    }
+
+    #[test]
+    fn search_query_get_ranking_info() {
+        let query = |get_ranking_info| SearchQuery::<String> {
+            get_ranking_info,
+            ..SearchQuery::default()
+        };
+
+        assert_eq!(query(None).to_params_string(), "");
+        assert_eq!(query(Some(true)).to_params_string(), "getRankingInfo=true");
+        assert_eq!(
+            query(Some(false)).to_params_string(),
+            "getRankingInfo=false"
+        );
+    }
+
+    #[test]
+    fn settings_custom_normalization() {
+        let replacements = vec![("ä", "ae"), ("ö", "oe"), ("ü", "ue")]
+            .into_iter()
+            .map(|(from, to)| (from.to_owned(), to.to_owned()))
+            .collect();
+
+        let mut normalization = HashMap::new();
+        normalization.insert("default".to_owned(), replacements);
+
+        assert_eq!(
+            serde_json::to_value(SetSettings::patch().with_custom_normalization(normalization))
+                .unwrap(),
+            serde_json::json!({
+                "customNormalization": { "default": { "ä": "ae", "ö": "oe", "ü": "ue" } }
+            })
+        );
+    }
+
+    #[test]
+    fn settings_numeric_attributes_for_filtering() {
+        let settings = SetSettings::patch().with_numeric_attributes_for_filtering(vec![
+            NumericAttribute::new(Attribute("price".to_owned())),
+            NumericAttribute::equal_only(Attribute("year".to_owned())),
+        ]);
+
+        assert_eq!(
+            serde_json::to_string(&settings).unwrap(),
+            r#"{"numericAttributesForFiltering":["price","equalOnly(year)"]}"#
+        );
+    }
+
+    #[test]
+    fn search_query_analytics_tags() {
+        let mut query = SearchQuery::<String>::default();
+        query.add_analytics_tag("mobile".to_owned()).unwrap();
+        query.add_analytics_tag("country:fr".to_owned()).unwrap();
+
+        assert_eq!(
+            query.add_analytics_tag("".to_owned()),
+            Err(InvalidAnalyticsTag::Empty)
+        );
+        assert_eq!(
+            query.add_analytics_tag("a".repeat(101)),
+            Err(InvalidAnalyticsTag::TooLong)
+        );
+        assert_eq!(
+            query.add_analytics_tag("a\nb".to_owned()),
+            Err(InvalidAnalyticsTag::ControlCharacter)
+        );
+
+        assert_eq!(
+            query.to_params_string(),
+            "analyticsTags=%5B%22mobile%22%2C%22country%3Afr%22%5D"
+        );
+
+        query.add_analytics_tag("a".repeat(100)).unwrap();
+        for i in 3..10 {
+            query.add_analytics_tag(i.to_string()).unwrap();
+        }
+
+        assert_eq!(
+            query.add_analytics_tag("one-too-many".to_owned()),
+            Err(InvalidAnalyticsTag::TooMany)
+        );
+        assert_eq!(query.analytics_tags.map(|it| it.len()), Some(10));
+    }
+
+    #[test]
+    fn copy_index_operation() {
+        assert_eq!(
+            serde_json::to_value(IndexOperation::copy("prod", &[])).unwrap(),
+            serde_json::json!({ "operation": "copy", "destination": "prod" })
+        );
+
+        assert_eq!(
+            serde_json::to_value(IndexOperation::copy("prod", &[CopyScope::Settings])).unwrap(),
+            serde_json::json!({ "operation": "copy", "destination": "prod", "scope": ["settings"] })
+        );
+
+        assert_eq!(
+            serde_json::to_value(IndexOperation::copy(
+                "prod",
+                &[CopyScope::Synonyms, CopyScope::Rules]
+            ))
+            .unwrap()["scope"],
+            serde_json::json!(["synonyms", "rules"])
+        );
+    }
+
+    #[test]
+    fn partial_update_batch_requests() {
+        let body = PartialUpdateBody::new()
+            .decrement("stock".to_owned(), 2)
+            .operation(
+                "tags".to_owned(),
+                PartialUpdateOperation::AddUnique("sale".into()),
+            )
+            .set("price".to_owned(), 10.into());
+
+        insta::assert_json_snapshot!(vec![
+            body.clone().into_batch_request("sku-1".to_owned(), true),
+            body.into_batch_request("sku-2".to_owned(), false),
+        ]);
+    }
+}
@@ -1,13 +1,17 @@
-use crate::model::task::{TaskId, TaskStatus};
+use crate::{
+    model::task::{TaskId, TaskStatus},
+    request::{BatchWriteRequest, SetSettings},
+};
 use chrono::{DateTime, Utc};
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 
 /// A unit struct (like `()`), but as a standard struct with no fields,
 /// this allows for serde to "flatten" with it (a no-op, given the lack of anything to {de,}serialize)
 #[derive(Deserialize, Debug)]
 pub struct FlattenEmpty {}
 
-#[derive(Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
 #[serde(rename_all = "camelCase")]
 pub struct ObjectUpdateResponse {
     pub updated_at: DateTime<Utc>,
@@ -19,7 +23,20 @@ pub struct ObjectUpdateResponse {
     pub object_id: String,
 }
 
-#[derive(Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct ObjectCreateResponse {
+    pub created_at: DateTime<Utc>,
+
+    #[serde(rename = "taskID")]
+    pub task_id: TaskId,
+
+    /// The object ID generated by algolia.
+    #[serde(rename = "objectID")]
+    pub object_id: String,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
 #[serde(rename_all = "camelCase")]
 pub struct ObjectDeleteResponse {
     pub deleted_at: DateTime<Utc>,
@@ -28,21 +45,37 @@ pub struct ObjectDeleteResponse {
     pub task_id: TaskId,
 }
 
+/// Facet counts as returned by algolia: facet name -> facet value -> number of hits with that value.
+pub type DefaultFacets = HashMap<String, HashMap<String, usize>>;
+
+/// `T` is the type of the records, `F` the type the facet counts are deserialized into.
+///
+/// Apps with a fixed set of facets can use their own type for `F` instead of the `HashMap`s,
+/// e.g. a struct with a `brand: HashMap<String, usize>` field.
 #[derive(Deserialize, Debug)]
 #[serde(rename_all = "camelCase")]
-pub struct SearchResponse<T = FlattenEmpty> {
+pub struct SearchResponse<T = FlattenEmpty, F = DefaultFacets> {
     pub hits: Vec<Hit<T>>,
 
     pub page: usize,
 
+    /// The number of matching objects, which can be more than can be paginated through,
+    /// see `SearchResponse::has_more_within_limit`.
     #[serde(rename = "nbHits")]
     pub hit_count: usize,
 
+    /// The number of pages that can be retrieved, which accounts for `paginationLimitedTo`.
     #[serde(rename = "nbPages")]
     pub page_count: usize,
 
     pub hits_per_page: usize,
 
+    /// The `offset` of the request, when searching by offset instead of `page`.
+    pub offset: Option<usize>,
+
+    /// The `length` of the request, when searching by offset instead of `page`.
+    pub length: Option<usize>,
+
     #[serde(rename = "processingTimeMS")]
     pub processing_time_ms: usize,
 
@@ -51,6 +84,84 @@ pub struct SearchResponse<T = FlattenEmpty> {
     pub parsed_query: Option<String>,
 
     pub params: String,
+
+    /// Only present when the search was made with `click_analytics` enabled.
+    #[serde(rename = "queryID")]
+    pub query_id: Option<String>,
+
+    /// Facet counts, only present when the search requested `facets`.
+    pub facets: Option<F>,
+}
+
+impl<T, F> SearchResponse<T, F> {
+    /// Take the hits out of the response.
+    pub fn into_hits(self) -> Vec<Hit<T>> {
+        self.hits
+    }
+
+    /// Take the records out of the response, dropping the per-hit metadata.
+    pub fn into_inner_vec(self) -> Vec<T> {
+        self.hits.into_iter().map(|it| it.inner).collect()
+    }
+
+    /// What Insights events about the hit `object_id` need, `None` if it isn't part of this page,
+    /// or if the search wasn't made with `click_analytics` enabled.
+    ///
+    /// The position accounts for the `offset` of the search when there is one, and for the `page` otherwise.
+    pub fn hit_context(&self, object_id: &str) -> Option<HitContext> {
+        let query_id = self.query_id.as_ref()?;
+        let index = self.hits.iter().position(|it| it.object_id == object_id)?;
+        let first = self.offset.unwrap_or(self.page * self.hits_per_page);
+
+        Some(HitContext {
+            query_id: query_id.clone(),
+            position: first + index + 1,
+            object_id: object_id.to_owned(),
+        })
+    }
+
+    /// Whether the next page has hits.
+    ///
+    /// Algolia stops returning hits after the index's `paginationLimitedTo` (1000 by default) hits,
+    /// even if `hit_count` is higher, so checking `hit_count` isn't enough. Use `Client::browse` to go past the limit.
+    pub fn has_more_within_limit(&self) -> bool {
+        self.page + 1 < self.page_count
+    }
+}
+
+impl<T, F> IntoIterator for SearchResponse<T, F> {
+    type Item = Hit<T>;
+    type IntoIter = std::vec::IntoIter<Hit<T>>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.hits.into_iter()
+    }
+}
+
+/// Several search responses, as returned by the multiple queries endpoint: `{"results": [...]}`.
+#[derive(Deserialize, Debug)]
+pub struct SearchResults<T = FlattenEmpty, F = DefaultFacets> {
+    pub results: Vec<SearchResponse<T, F>>,
+}
+
+impl<T, F> IntoIterator for SearchResults<T, F> {
+    type Item = SearchResponse<T, F>;
+    type IntoIter = std::vec::IntoIter<SearchResponse<T, F>>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.results.into_iter()
+    }
+}
+
+/// A hit as referenced by Insights events, see `SearchResponse::hit_context`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HitContext {
+    pub query_id: String,
+
+    /// The position of the hit in the whole results (not just its page), starting at 1.
+    pub position: usize,
+
+    pub object_id: String,
 }
 
 #[derive(Deserialize, Debug)]
@@ -59,12 +170,13 @@ pub struct Hit<T> {
     #[serde(rename = "objectID")]
     pub object_id: String,
 
-    // fixme: fix this and reimplement.
-    // // todo: this can be single OR Vec, handle both cases
-    // #[serde(rename = "_highlightResult")]
-    // #[serde(default)]
-    // pub highlight_result: HashMap<String, HighlightResult>,
+    /// The highlighted attributes, see `Hit::highlighted`.
+    /// Attributes whose highlighting can't be parsed are left out, instead of failing the whole response.
+    #[serde(rename = "_highlightResult")]
+    #[serde(default, deserialize_with = "lenient_highlights")]
+    pub highlight_result: HashMap<String, Highlight>,
 
+    // fixme: fix this and reimplement, like `highlight_result`.
     // #[serde(rename = "_snippetResult")]
     // #[serde(default)]
     // pub snippet_result: HashMap<String, SnippetResult>,
@@ -78,6 +190,69 @@ pub struct Hit<T> {
     pub inner: T,
 }
 
+impl<T> Hit<T> {
+    /// The highlighted HTML of `attribute` (e.g. `"title"`, or `"author.name"` for nested attributes,
+    /// `"tags.0"` for array elements), if it's part of the highlighted attributes.
+    pub fn highlighted(&self, attribute: &str) -> Option<&str> {
+        let mut path = attribute.split('.');
+        let mut highlight = self.highlight_result.get(path.next()?)?;
+
+        for segment in path {
+            highlight = match highlight {
+                Highlight::Nested(attributes) => attributes.get(segment)?,
+                Highlight::List(elements) => elements.get(segment.parse::<usize>().ok()?)?,
+                Highlight::Value(_) => return None,
+            };
+        }
+
+        match highlight {
+            Highlight::Value(result) => Some(&result.value),
+            _ => None,
+        }
+    }
+}
+
+/// A record along with the fields `T` doesn't know about, so that they aren't lost
+/// when writing the record back (after a migration adding fields, for example).
+///
+/// `T` must be a plain struct: `extra` is whatever's left once `T` took its fields.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct WithExtra<T> {
+    #[serde(flatten)]
+    pub inner: T,
+
+    #[serde(flatten)]
+    pub extra: serde_json::Map<String, serde_json::Value>,
+}
+
+/// A hit which keeps the fields `T` doesn't know about in `inner.extra`.
+pub type HitWithExtra<T> = Hit<WithExtra<T>>;
+
+/// The highlighting of an attribute, which mirrors the attribute's shape.
+#[derive(Deserialize, Debug)]
+#[serde(untagged)]
+pub enum Highlight {
+    Value(HighlightResult),
+    List(Vec<Highlight>),
+    Nested(HashMap<String, Highlight>),
+}
+
+fn lenient_highlights<'de, D>(deserializer: D) -> Result<HashMap<String, Highlight>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    let highlights: Option<HashMap<String, serde_json::Value>> =
+        Deserialize::deserialize(deserializer)?;
+
+    Ok(highlights
+        .unwrap_or_default()
+        .into_iter()
+        .filter_map(|(attribute, highlight)| {
+            Some((attribute, serde_json::from_value(highlight).ok()?))
+        })
+        .collect())
+}
+
 #[derive(Eq, PartialEq, Deserialize, Debug)]
 #[serde(rename_all = "camelCase")]
 pub enum MatchLevel {
@@ -120,14 +295,27 @@ pub struct RankingInfo {
     pub exact_word_count: usize,
 }
 
-#[derive(Deserialize, Debug)]
+/// The status of a task, see `Client::wait_for_task_status`.
+#[derive(Deserialize, Debug, Copy, Clone, PartialEq, Eq)]
 #[serde(rename_all = "camelCase")]
-pub(crate) struct TaskStatusResponse {
+pub struct TaskStatusResponse {
     pub status: TaskStatus,
+
+    /// Whether the index has tasks that aren't published yet, this one or others.
     pub pending_task: bool,
 }
 
-#[derive(Deserialize, Debug)]
+/// The response of `Client::copy_index`, whose task is on the source index.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct IndexOperationResponse {
+    pub updated_at: DateTime<Utc>,
+
+    #[serde(rename = "taskID")]
+    pub task_id: TaskId,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
 #[serde(rename_all = "camelCase")]
 pub struct SettingsUpdateResponse {
     pub updated_at: DateTime<Utc>,
@@ -136,7 +324,133 @@ pub struct SettingsUpdateResponse {
     pub task_id: TaskId,
 }
 
+/// The current settings of an index, as returned by `Client::get_settings`.
+///
+/// Settings are kept as raw JSON, keyed by their (camelCase) name, so that settings this crate doesn't model yet survive.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Default)]
+#[serde(transparent)]
+pub struct IndexSettings(serde_json::Map<String, serde_json::Value>);
+
+impl IndexSettings {
+    /// The value of the setting `name` (e.g. `"searchableAttributes"`), if it's set.
+    pub fn get(&self, name: &str) -> Option<&serde_json::Value> {
+        self.0.get(name).filter(|it| !it.is_null())
+    }
+
+    /// What applying `desired` to these settings would change.
+    ///
+    /// Only the settings that `desired` sets are compared, since the others are left as-is by `Client::set_settings`.
+    pub fn diff(&self, desired: &SetSettings) -> SettingsDiff {
+        let desired = match serde_json::to_value(desired) {
+            Ok(serde_json::Value::Object(desired)) => desired,
+            // `SetSettings` always serializes to an object.
+            _ => unreachable!(),
+        };
+
+        let changes = desired
+            .into_iter()
+            .filter_map(|(field, new)| {
+                let old = self.get(&field).cloned();
+                if old.as_ref() == Some(&new) {
+                    return None;
+                }
+
+                Some(SettingChange { field, old, new })
+            })
+            .collect();
+
+        SettingsDiff { changes }
+    }
+}
+
+/// See `Client::index_info`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct IndexInfo {
+    /// The number of objects in the index, as the `nbHits` of an empty query.
+    pub object_count: usize,
+
+    pub settings: IndexSettings,
+}
+
+/// The difference between an index's settings and a `SetSettings`, see `IndexSettings::diff`.
+#[derive(Serialize, Debug, Clone, PartialEq, Default)]
+pub struct SettingsDiff {
+    pub changes: Vec<SettingChange>,
+}
+
+impl SettingsDiff {
+    /// Whether applying the settings would be a no-op.
+    pub fn is_empty(&self) -> bool {
+        self.changes.is_empty()
+    }
+}
+
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct SettingChange {
+    /// The (camelCase) name of the setting.
+    pub field: String,
+
+    /// The current value, `None` if the setting isn't set on the index.
+    pub old: Option<serde_json::Value>,
+
+    pub new: serde_json::Value,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct SynonymsUpdateResponse {
+    pub updated_at: DateTime<Utc>,
+
+    #[serde(rename = "taskID")]
+    pub task_id: TaskId,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct SynonymDeleteResponse {
+    pub deleted_at: DateTime<Utc>,
+
+    #[serde(rename = "taskID")]
+    pub task_id: TaskId,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct RulesUpdateResponse {
+    pub updated_at: DateTime<Utc>,
+
+    #[serde(rename = "taskID")]
+    pub task_id: TaskId,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct RuleDeleteResponse {
+    pub deleted_at: DateTime<Utc>,
+
+    #[serde(rename = "taskID")]
+    pub task_id: TaskId,
+}
+
+/// The result of a conditional fetch, see `Client::get_object_if_none_match`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Conditional<T> {
+    /// The object changed (or no `ETag` was given).
+    Modified {
+        object: T,
+        /// Send this back on the next fetch to only get the object if it changed again.
+        etag: Option<String>,
+    },
+    /// The object still matches the given `ETag`, use the cached copy.
+    NotModified,
+}
+
 #[derive(Deserialize, Debug)]
+pub struct GetObjectsResponse<T> {
+    pub results: Vec<T>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
 #[serde(rename_all = "camelCase")]
 pub struct BatchWriteResponse {
     #[serde(rename = "taskID")]
@@ -146,3 +460,409 @@ pub struct BatchWriteResponse {
     #[serde(default)]
     pub object_ids: Vec<String>,
 }
+
+impl BatchWriteResponse {
+    /// The object IDs generated for the `AddObject` operations of `requests` (the operations
+    /// this is the response to), along with the index of the operation in `requests`.
+    ///
+    /// `object_ids` has an ID per operation, in order, so the other operations are skipped.
+    pub fn added_object_ids(&self, requests: &[BatchWriteRequest]) -> Vec<(usize, String)> {
+        requests
+            .iter()
+            .zip(&self.object_ids)
+            .enumerate()
+            .filter(|(_, (request, _))| matches!(request, BatchWriteRequest::AddObject { .. }))
+            .map(|(idx, (_, object_id))| (idx, object_id.clone()))
+            .collect()
+    }
+}
+
+/// A page of `Client::browse`.
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct BrowseResponse<T = FlattenEmpty> {
+    pub hits: Vec<Hit<T>>,
+
+    /// Pass this to `Client::browse` to get the next page, `None` on the last page.
+    pub cursor: Option<String>,
+
+    /// The `offset` of the request, when browsing by offset.
+    pub offset: Option<usize>,
+
+    /// The `length` of the request, when browsing by offset.
+    pub length: Option<usize>,
+
+    /// The total number of objects matching the query.
+    #[serde(rename = "nbHits")]
+    pub hit_count: usize,
+
+    #[serde(rename = "processingTimeMS")]
+    pub processing_time_ms: usize,
+}
+
+/// What `Client::delete_objects_by_filter` deleted.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DeleteByFilterResponse {
+    pub object_ids: Vec<String>,
+
+    /// One response per batch of deletions, wait for all of their tasks to make sure the objects are gone.
+    pub batches: Vec<BatchWriteResponse>,
+}
+
+/// What `Client::import_ndjson` imported.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct BatchImportSummary {
+    /// The number of objects sent to algolia.
+    pub imported: usize,
+
+    /// One response per batch, wait for all of their tasks to make sure the objects are indexed.
+    pub batches: Vec<BatchWriteResponse>,
+
+    /// The lines that were skipped.
+    pub errors: Vec<ImportLineError>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ImportLineError {
+    /// Starting at 1.
+    pub line: usize,
+
+    pub message: String,
+}
+
+#[cfg(test)]
+mod test {
+    use super::{
+        BatchWriteResponse, BrowseResponse, Hit, HitContext, HitWithExtra, IndexSettings,
+        ObjectUpdateResponse, SearchResponse, SearchResults,
+    };
+    use crate::{
+        model::attribute::{Attribute, FacetAttribute},
+        request::{BatchWriteRequest, SetSettings},
+    };
+    use std::collections::HashMap;
+
+    #[test]
+    fn object_update_round_trip() {
+        let resp: ObjectUpdateResponse = serde_json::from_str(
+            r#"{"updatedAt":"2021-08-06T12:00:00Z","taskID":1234,"objectID":"abc"}"#,
+        )
+        .unwrap();
+
+        let json = serde_json::to_string(&resp).unwrap();
+
+        assert_eq!(
+            serde_json::from_str::<ObjectUpdateResponse>(&json).unwrap(),
+            resp
+        );
+    }
+
+    #[test]
+    fn batch_write_round_trip() {
+        let resp: BatchWriteResponse =
+            serde_json::from_str(r#"{"taskID":1234,"objectIDs":["a","b"]}"#).unwrap();
+
+        let json = serde_json::to_string(&resp).unwrap();
+
+        assert_eq!(
+            serde_json::from_str::<BatchWriteResponse>(&json).unwrap(),
+            resp
+        );
+        assert_eq!(resp.clone(), resp);
+    }
+
+    #[test]
+    fn added_object_ids() {
+        let resp: BatchWriteResponse =
+            serde_json::from_str(r#"{"taskID":1234,"objectIDs":["gen-1","b","gen-2"]}"#).unwrap();
+
+        let requests = [
+            BatchWriteRequest::AddObject {
+                body: serde_json::Map::new(),
+            },
+            BatchWriteRequest::DeleteObject {
+                object_id: "b".to_owned(),
+            },
+            BatchWriteRequest::AddObject {
+                body: serde_json::Map::new(),
+            },
+        ];
+
+        assert_eq!(
+            resp.added_object_ids(&requests),
+            [(0, "gen-1".to_owned()), (2, "gen-2".to_owned())]
+        );
+    }
+
+    #[derive(serde::Deserialize, Debug, PartialEq)]
+    struct Record {
+        name: String,
+    }
+
+    fn search_response() -> SearchResponse<Record> {
+        serde_json::from_value(serde_json::json!({
+            "hits": [
+                { "objectID": "a", "name": "first" },
+                { "objectID": "b", "name": "second" },
+            ],
+            "page": 0,
+            "nbHits": 2,
+            "nbPages": 1,
+            "hitsPerPage": 20,
+            "processingTimeMS": 1,
+            "query": "",
+            "params": "",
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn search_response_into_hits() {
+        let ids = search_response()
+            .into_iter()
+            .map(|it| it.object_id)
+            .collect::<Vec<_>>();
+
+        assert_eq!(ids, ["a", "b"]);
+        assert_eq!(search_response().into_hits().len(), 2);
+    }
+
+    #[test]
+    fn search_response_into_inner_vec() {
+        assert_eq!(
+            search_response().into_inner_vec(),
+            [
+                Record {
+                    name: "first".to_owned()
+                },
+                Record {
+                    name: "second".to_owned()
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn search_response_typed_facets() {
+        #[derive(serde::Deserialize, Debug, PartialEq)]
+        struct Facets {
+            brand: HashMap<String, usize>,
+        }
+
+        let resp: SearchResponse<Record, Facets> = serde_json::from_value(serde_json::json!({
+            "hits": [],
+            "page": 0,
+            "nbHits": 3,
+            "nbPages": 1,
+            "hitsPerPage": 20,
+            "processingTimeMS": 1,
+            "query": "",
+            "params": "facets=%5B%22brand%22%5D",
+            "facets": { "brand": { "apple": 2, "samsung": 1 } },
+        }))
+        .unwrap();
+
+        assert_eq!(resp.facets.unwrap().brand["apple"], 2);
+        assert!(search_response().facets.is_none());
+    }
+
+    #[test]
+    fn settings_diff() {
+        let live: IndexSettings = serde_json::from_value(serde_json::json!({
+            "searchableAttributes": ["title"],
+            "attributesForFaceting": ["brand"],
+            "customRanking": null,
+        }))
+        .unwrap();
+
+        assert!(live.diff(&SetSettings::patch()).is_empty());
+
+        let unchanged = SetSettings::patch()
+            .with_attributes_for_faceting(vec![FacetAttribute::new(Attribute("brand".to_owned()))]);
+        assert!(live.diff(&unchanged).is_empty());
+
+        let changed =
+            SetSettings::patch().with_attributes_for_faceting(vec![FacetAttribute::filter_only(
+                Attribute("brand".to_owned()),
+            )]);
+        insta::assert_json_snapshot!(live.diff(&changed));
+    }
+
+    #[test]
+    fn hit_highlighted() {
+        let hit: Hit<serde_json::Value> = serde_json::from_value(serde_json::json!({
+            "objectID": "1",
+            "title": "Blue shoes",
+            "_highlightResult": {
+                "title": {
+                    "value": "Blue <em>shoes</em>",
+                    "matchLevel": "full",
+                    "matchedWords": ["shoes"],
+                },
+                "author": {
+                    "name": { "value": "Jane", "matchLevel": "none", "matchedWords": [] },
+                },
+                "tags": [
+                    { "value": "<em>shoes</em>", "matchLevel": "full", "matchedWords": ["shoes"] },
+                ],
+            },
+        }))
+        .unwrap();
+
+        assert_eq!(hit.highlighted("title"), Some("Blue <em>shoes</em>"));
+        assert_eq!(hit.highlighted("author.name"), Some("Jane"));
+        assert_eq!(hit.highlighted("tags.0"), Some("<em>shoes</em>"));
+        assert_eq!(hit.highlighted("author"), None);
+        assert_eq!(hit.highlighted("tags.1"), None);
+        assert_eq!(hit.highlighted("description"), None);
+        assert_eq!(hit.inner["title"], "Blue shoes");
+    }
+
+    #[test]
+    fn hit_highlighted_skips_invalid() {
+        let hit: Hit<serde_json::Value> = serde_json::from_value(serde_json::json!({
+            "objectID": "1",
+            "_highlightResult": {
+                "title": { "value": "Blue <em>shoes</em>", "matchLevel": "full" },
+                "price": { "value": "10", "matchLevel": "unknown" },
+                "stock": 3,
+            },
+        }))
+        .unwrap();
+
+        assert_eq!(hit.highlighted("title"), Some("Blue <em>shoes</em>"));
+        assert_eq!(hit.highlighted("price"), None);
+        assert_eq!(hit.highlight_result.len(), 1);
+
+        let hit: Hit<serde_json::Value> = serde_json::from_value(
+            serde_json::json!({ "objectID": "1", "_highlightResult": null }),
+        )
+        .unwrap();
+        assert!(hit.highlight_result.is_empty());
+    }
+
+    #[test]
+    fn search_response_has_more_within_limit() {
+        let page = |page| {
+            serde_json::from_value::<SearchResponse>(serde_json::json!({
+                "hits": [],
+                "page": page,
+                // more hits than the default `paginationLimitedTo` of 1000.
+                "nbHits": 5000,
+                "nbPages": 50,
+                "hitsPerPage": 20,
+                "processingTimeMS": 1,
+                "query": "",
+                "params": "",
+            }))
+            .unwrap()
+        };
+
+        assert!(page(0).has_more_within_limit());
+        assert!(page(48).has_more_within_limit());
+        assert!(!page(49).has_more_within_limit());
+        assert!(!search_response().has_more_within_limit());
+    }
+
+    #[test]
+    fn browse_response_offset() {
+        let page: BrowseResponse = serde_json::from_value(serde_json::json!({
+            "hits": [{ "objectID": "a" }],
+            "nbHits": 120,
+            "offset": 100,
+            "length": 50,
+            "processingTimeMS": 1,
+            "params": "offset=100&length=50",
+        }))
+        .unwrap();
+
+        assert_eq!((page.offset, page.length), (Some(100), Some(50)));
+        assert_eq!(page.cursor, None);
+        assert_eq!(page.hits[0].object_id, "a");
+    }
+
+    #[test]
+    fn search_response_hit_context() {
+        let mut resp = search_response();
+        assert_eq!(resp.hit_context("b"), None);
+
+        resp.query_id = Some("43b15df305339e827f0ac0bdc5ebcaa7".to_owned());
+        resp.page = 2;
+
+        assert_eq!(
+            resp.hit_context("b"),
+            Some(HitContext {
+                query_id: "43b15df305339e827f0ac0bdc5ebcaa7".to_owned(),
+                position: 42,
+                object_id: "b".to_owned(),
+            })
+        );
+        assert_eq!(resp.hit_context("c"), None);
+
+        // algolia reports the page containing `offset`, which may not start at it.
+        resp.offset = Some(45);
+        resp.length = Some(10);
+        assert_eq!(resp.hit_context("b").unwrap().position, 47);
+    }
+
+    #[test]
+    fn search_results_envelope() {
+        let response = serde_json::json!({
+            "hits": [{ "objectID": "a" }],
+            "page": 0,
+            "nbHits": 1,
+            "nbPages": 1,
+            "hitsPerPage": 20,
+            "processingTimeMS": 1,
+            "query": "",
+            "params": "",
+        });
+
+        let results: SearchResults = serde_json::from_value(
+            serde_json::json!({ "results": [response.clone(), response.clone()] }),
+        )
+        .unwrap();
+        assert_eq!(results.results.len(), 2);
+        assert_eq!(results.into_iter().next().unwrap().hits[0].object_id, "a");
+
+        // the errors of the responses aren't hidden.
+        let mut invalid = response;
+        invalid.as_object_mut().unwrap().remove("query");
+
+        let err =
+            serde_json::from_value::<SearchResults>(serde_json::json!({ "results": [invalid] }))
+                .unwrap_err();
+        assert!(err.to_string().contains("missing field `query`"), "{}", err);
+    }
+
+    #[test]
+    fn hit_with_extra() {
+        #[derive(serde::Serialize, serde::Deserialize, Debug, PartialEq)]
+        struct Product {
+            name: String,
+        }
+
+        let hit: HitWithExtra<Product> = serde_json::from_value(serde_json::json!({
+            "objectID": "a",
+            "name": "shoes",
+            "color": "blue",
+            "sizes": [40, 41],
+            "_highlightResult": {},
+        }))
+        .unwrap();
+
+        assert_eq!(hit.object_id, "a");
+        assert_eq!(
+            hit.inner.inner,
+            Product {
+                name: "shoes".to_owned()
+            }
+        );
+
+        // writing it back keeps the fields `Product` doesn't have.
+        assert_eq!(
+            serde_json::to_value(&hit.inner).unwrap(),
+            serde_json::json!({ "name": "shoes", "color": "blue", "sizes": [40, 41] })
+        );
+    }
+}
@@ -0,0 +1,88 @@
+//! Latency of the searches made by a `Client`, see `Client::with_search_stats`.
+
+use std::{
+    sync::{Arc, Mutex, PoisonError},
+    time::Duration,
+};
+
+/// Accumulates the latency of searches.
+///
+/// Clones share the same totals, so keep one around to read them while the client records into another.
+#[derive(Clone, Debug, Default)]
+pub struct SearchStats(Arc<Mutex<SearchTotals>>);
+
+impl SearchStats {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The totals recorded so far.
+    pub fn totals(&self) -> SearchTotals {
+        *self.0.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    /// Get the totals recorded so far and start again from zero, e.g. to report them periodically.
+    pub fn reset(&self) -> SearchTotals {
+        std::mem::take(&mut *self.0.lock().unwrap_or_else(PoisonError::into_inner))
+    }
+
+    pub(crate) fn record(&self, processing_time_ms: usize, elapsed: Duration) {
+        let mut totals = self.0.lock().unwrap_or_else(PoisonError::into_inner);
+
+        totals.searches += 1;
+        totals.processing_time += Duration::from_millis(processing_time_ms as u64);
+        totals.elapsed += elapsed;
+        totals.max_elapsed = totals.max_elapsed.max(elapsed);
+    }
+}
+
+/// The totals of the successful searches recorded by `SearchStats`.
+#[derive(Debug, Default, Copy, Clone, Eq, PartialEq)]
+pub struct SearchTotals {
+    pub searches: u32,
+
+    /// The time algolia spent on the searches (their `processingTimeMS`).
+    pub processing_time: Duration,
+
+    /// The time the searches took from the client's side, including the network and retries.
+    pub elapsed: Duration,
+
+    /// The longest `elapsed` of a single search.
+    pub max_elapsed: Duration,
+}
+
+impl SearchTotals {
+    pub fn average_processing_time(&self) -> Option<Duration> {
+        self.processing_time.checked_div(self.searches)
+    }
+
+    pub fn average_elapsed(&self) -> Option<Duration> {
+        self.elapsed.checked_div(self.searches)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::SearchStats;
+    use std::time::Duration;
+
+    #[test]
+    fn search_stats() {
+        let stats = SearchStats::new();
+        assert_eq!(stats.totals().average_elapsed(), None);
+
+        stats.clone().record(2, Duration::from_millis(30));
+        stats.record(4, Duration::from_millis(50));
+
+        let totals = stats.reset();
+        assert_eq!(totals.searches, 2);
+        assert_eq!(
+            totals.average_processing_time(),
+            Some(Duration::from_millis(3))
+        );
+        assert_eq!(totals.average_elapsed(), Some(Duration::from_millis(40)));
+        assert_eq!(totals.max_elapsed, Duration::from_millis(50));
+
+        assert_eq!(stats.totals().searches, 0);
+    }
+}